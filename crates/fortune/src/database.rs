@@ -0,0 +1,204 @@
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Cursor, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use crate::index::{entries, is_delimiter, text, Index};
+use crate::{Filter, FortuneError};
+
+/// The fortunes of one database file.
+#[derive(Debug)]
+pub(crate) struct Database {
+    entries: Entries,
+}
+
+#[derive(Debug)]
+enum Entries {
+    /// Every fortune, from scanning the whole database.
+    Scanned(Vec<String>),
+    /// Where each fortune starts in the database at `path`, from its strfile index, so only
+    /// the chosen one has to be read.
+    Indexed { path: PathBuf, index: Index },
+}
+
+impl Database {
+    pub(crate) fn parse(content: &[u8]) -> Self {
+        let fortunes = entries(content, b'%')
+            .into_iter()
+            .map(|range| text(&content[range], false))
+            .collect();
+        Self {
+            entries: Entries::Scanned(fortunes),
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn from_fortunes(fortunes: Vec<String>) -> Self {
+        Self {
+            entries: Entries::Scanned(fortunes),
+        }
+    }
+
+    /// Open the database at `file_path`, using its strfile index when it has an up to date one
+    /// and scanning it otherwise.
+    pub(crate) fn open(file_path: &Path) -> io::Result<Self> {
+        let path = file_path.display();
+        if !file_path.exists() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("The fortune file '{path}' does not exist"),
+            ));
+        }
+        if file_path.is_dir() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("'{path}' is a directory, not a file"),
+            ));
+        }
+
+        if let Some(index) = current_index(file_path) {
+            return Ok(Self {
+                entries: Entries::Indexed {
+                    path: file_path.to_path_buf(),
+                    index,
+                },
+            });
+        }
+
+        let mut content = Vec::new();
+        File::open(file_path)?.read_to_end(&mut content)?;
+        Ok(Self::parse(&content))
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        match &self.entries {
+            Entries::Scanned(fortunes) => fortunes.len(),
+            Entries::Indexed { index, .. } => index.len(),
+        }
+    }
+
+    /// A database of only the fortunes `filter` matches, read into memory.
+    pub(crate) fn filtered(self, filter: &Filter) -> Result<Self, FortuneError> {
+        let fortunes = match self.entries {
+            Entries::Scanned(fortunes) => fortunes,
+            Entries::Indexed { path, index } => {
+                let read = |source| FortuneError::Read {
+                    path: path.clone(),
+                    source,
+                };
+                let mut content = Vec::new();
+                File::open(&path)
+                    .and_then(|mut file| file.read_to_end(&mut content))
+                    .map_err(read)?;
+                let mut reader = Cursor::new(content);
+                (0..index.len())
+                    .map(|position| read_entry(&mut reader, &index, position))
+                    .collect::<io::Result<_>>()
+                    .map_err(read)?
+            }
+        };
+
+        Ok(Self {
+            entries: Entries::Scanned(
+                fortunes
+                    .into_iter()
+                    .filter(|fortune| filter.matches(fortune))
+                    .collect(),
+            ),
+        })
+    }
+
+    /// The fortune at `position`, which must be less than [`Database::len`].
+    pub(crate) fn get(&self, position: usize) -> Result<String, FortuneError> {
+        match &self.entries {
+            Entries::Scanned(fortunes) => Ok(fortunes[position].clone()),
+            Entries::Indexed { path, index } => File::open(path)
+                .and_then(|file| read_entry(&mut BufReader::new(file), index, position))
+                .map_err(|source| FortuneError::Read {
+                    path: path.clone(),
+                    source,
+                }),
+        }
+    }
+}
+
+/// Read the fortune at `position` in the database `reader` reads, seeking to where `index`
+/// says it starts.
+fn read_entry(
+    reader: &mut (impl BufRead + Seek),
+    index: &Index,
+    position: usize,
+) -> io::Result<String> {
+    reader.seek(SeekFrom::Start(index.offsets[position].into()))?;
+    let mut bytes = Vec::new();
+    let mut line = Vec::new();
+    while reader.read_until(b'\n', &mut line)? > 0 {
+        if is_delimiter(&line, index.delimiter) {
+            break;
+        }
+        bytes.append(&mut line);
+    }
+    Ok(text(&bytes, index.flags.rotated))
+}
+
+/// The index alongside the database at `path`, unless there's none, it can't be read, or it's
+/// older than the database and so might point at the wrong places.
+fn current_index(path: &Path) -> Option<Index> {
+    let index_path = Index::path_for(path);
+    let modified = |path: &Path| fs::metadata(path).and_then(|metadata| metadata.modified());
+    if modified(&index_path).ok()? < modified(path).ok()? {
+        return None;
+    }
+
+    let index = Index::read(&mut BufReader::new(File::open(index_path).ok()?)).ok()?;
+    let length = fs::metadata(path).ok()?.len();
+    index
+        .offsets
+        .iter()
+        .all(|&offset| u64::from(offset) <= length)
+        .then_some(index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::Flags;
+    use crate::Pattern;
+
+    #[test]
+    fn reads_fortunes_through_index() {
+        let dir = std::env::temp_dir().join(format!("fortune-index-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("rotated");
+        fs::write(&path, "Uryyb\n%\nJbeyq\r\nntnva\r\n%\n").unwrap();
+
+        // Without an index the database is scanned, and rot13 can't be known
+        let scanned = Database::open(&path).unwrap();
+        assert_eq!(scanned.get(1).unwrap(), "Jbeyq\nntnva");
+
+        let flags = Flags {
+            rotated: true,
+            ..Flags::default()
+        };
+        let index = Index::build(&fs::read(&path).unwrap(), b'%', flags).unwrap();
+        index
+            .write(&mut File::create(Index::path_for(&path)).unwrap())
+            .unwrap();
+
+        let indexed = Database::open(&path).unwrap();
+        assert!(matches!(indexed.entries, Entries::Indexed { .. }));
+        assert_eq!(indexed.len(), 2);
+        assert_eq!(indexed.get(0).unwrap(), "Hello");
+        assert_eq!(indexed.get(1).unwrap(), "World\nagain");
+
+        // Filters see the decoded text
+        let filter = Filter {
+            include: Some(Pattern::try_from("again".to_string()).unwrap()),
+            ..Filter::default()
+        };
+        let filtered = indexed.filtered(&filter).unwrap();
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered.get(0).unwrap(), "World\nagain");
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,124 @@
+use regex::Regex;
+use serde::Deserialize;
+
+use crate::Weighting;
+
+/// How to load databases and which of their fortunes to choose from, beyond which databases.
+#[derive(Debug, Clone, Default)]
+pub struct Options {
+    pub weighting: Weighting,
+    pub offensive: Offensive,
+    pub filter: Filter,
+}
+
+/// Whether to choose from the offensive databases fortune keeps in `off` directories, which
+/// are usually rot13-encoded and marked so in their strfile index.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Offensive {
+    #[default]
+    Never,
+    /// Alongside the others, like `fortune -a`.
+    Also,
+    /// Instead of the others, like `fortune -o`.
+    Only,
+}
+
+/// Which fortunes to choose from, like fortune's `-s`, `-l` and `-m`. Lengths count characters.
+///
+/// Segments flatten it into their options, so it can't deny unknown fields itself.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Filter {
+    /// Skip fortunes with fewer characters than this.
+    pub min_length: Option<usize>,
+    /// Skip fortunes with more characters than this, e.g. to keep the MOTD short.
+    pub max_length: Option<usize>,
+    /// Only fortunes this matches.
+    pub include: Option<Pattern>,
+    /// No fortunes this matches.
+    pub exclude: Option<Pattern>,
+}
+
+impl Filter {
+    pub fn min_length(mut self, min_length: Option<usize>) -> Self {
+        self.min_length = min_length;
+        self
+    }
+
+    pub fn max_length(mut self, max_length: Option<usize>) -> Self {
+        self.max_length = max_length;
+        self
+    }
+
+    pub fn include(mut self, include: Option<Pattern>) -> Self {
+        self.include = include;
+        self
+    }
+
+    pub fn exclude(mut self, exclude: Option<Pattern>) -> Self {
+        self.exclude = exclude;
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.min_length.is_none()
+            && self.max_length.is_none()
+            && self.include.is_none()
+            && self.exclude.is_none()
+    }
+
+    pub fn matches(&self, fortune: &str) -> bool {
+        let length = fortune.chars().count();
+        self.min_length.is_none_or(|min| length >= min)
+            && self.max_length.is_none_or(|max| length <= max)
+            && self
+                .include
+                .as_ref()
+                .is_none_or(|pattern| pattern.0.is_match(fortune))
+            && !self
+                .exclude
+                .as_ref()
+                .is_some_and(|pattern| pattern.0.is_match(fortune))
+    }
+}
+
+/// A regular expression from configuration, checked when it's loaded. Use `(?i)` to ignore
+/// case, like `fortune -i`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(try_from = "String")]
+pub struct Pattern(Regex);
+
+impl TryFrom<String> for Pattern {
+    type Error = regex::Error;
+
+    fn try_from(pattern: String) -> Result<Self, Self::Error> {
+        Regex::new(&pattern).map(Self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_length_and_patterns() {
+        let filter = Filter {
+            max_length: Some(12),
+            exclude: Some(Pattern::try_from("(?i)monday".to_string()).unwrap()),
+            ..Filter::default()
+        };
+        assert!(filter.matches("Carpe diem."));
+        assert!(!filter.matches("I hate Mondays."));
+        assert!(!filter.matches("Brevity is the soul of wit."));
+
+        let filter = Filter {
+            min_length: Some(4),
+            include: Some(Pattern::try_from(r"\bcat".to_string()).unwrap()),
+            ..Filter::default()
+        };
+        assert!(filter.matches("The cat sat."));
+        assert!(!filter.matches("cat"));
+        assert!(!filter.matches("Concatenate strings."));
+    }
+}
@@ -0,0 +1,246 @@
+//! The `.dat` index `strfile` writes next to a fortune database, so a fortune can be read
+//! without scanning the whole database: a header, then where each fortune starts.
+
+use rand::seq::SliceRandom;
+use std::io::{self, Read, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+/// Version of the format `strfile` writes.
+const VERSION: u32 = 2;
+const HEADER_LEN: usize = 24;
+
+/// How an index and its database were written, as the header's flag bits.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Flags {
+    /// The offsets are shuffled (`strfile -r`).
+    pub random: bool,
+    /// The offsets are sorted by their fortunes (`strfile -o`).
+    pub ordered: bool,
+    /// The fortunes are rot13-encoded, like offensive databases (`strfile -x`).
+    pub rotated: bool,
+}
+
+impl Flags {
+    const RANDOM: u32 = 0x1;
+    const ORDERED: u32 = 0x2;
+    const ROTATED: u32 = 0x4;
+
+    fn from_bits(bits: u32) -> Self {
+        Self {
+            random: bits & Self::RANDOM != 0,
+            ordered: bits & Self::ORDERED != 0,
+            rotated: bits & Self::ROTATED != 0,
+        }
+    }
+
+    fn bits(self) -> u32 {
+        (self.random as u32 * Self::RANDOM)
+            | (self.ordered as u32 * Self::ORDERED)
+            | (self.rotated as u32 * Self::ROTATED)
+    }
+}
+
+/// A strfile index: where each fortune of a database starts, plus one offset past the last.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Index {
+    pub flags: Flags,
+    /// The character on a line of its own between fortunes, usually `%`.
+    pub delimiter: u8,
+    /// Bytes in the longest and shortest fortune.
+    pub longest: u32,
+    pub shortest: u32,
+    pub offsets: Vec<u32>,
+}
+
+impl Index {
+    /// Where the index for the database at `path` is kept: alongside it, with `.dat` appended.
+    pub fn path_for(database: &Path) -> PathBuf {
+        let mut path = database.as_os_str().to_owned();
+        path.push(".dat");
+        PathBuf::from(path)
+    }
+
+    /// Index `content`, a database whose fortunes are separated by `delimiter` lines, putting
+    /// the offsets in order (or shuffling them) as `flags` say.
+    pub fn build(content: &[u8], delimiter: u8, flags: Flags) -> io::Result<Self> {
+        let mut entries = entries(content, delimiter);
+        if flags.ordered {
+            entries.sort_by_cached_key(|range| text(&content[range.clone()], false).to_lowercase());
+        } else if flags.random {
+            entries.shuffle(&mut rand::thread_rng());
+        }
+
+        let offset = |position: usize| {
+            u32::try_from(position)
+                .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "database is over 4 GB"))
+        };
+        let mut offsets = entries
+            .iter()
+            .map(|range| offset(range.start))
+            .collect::<io::Result<Vec<_>>>()?;
+        offsets.push(offset(content.len())?);
+
+        let lengths = entries.iter().map(|range| range.len() as u32);
+        Ok(Self {
+            flags,
+            delimiter,
+            longest: lengths.clone().max().unwrap_or(0),
+            shortest: lengths.min().unwrap_or(0),
+            offsets,
+        })
+    }
+
+    /// How many fortunes the index covers.
+    pub fn len(&self) -> usize {
+        self.offsets.len().saturating_sub(1)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn read(reader: &mut impl Read) -> io::Result<Self> {
+        let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidData, message);
+
+        let mut header = [0; HEADER_LEN];
+        reader.read_exact(&mut header)?;
+        let field =
+            |index: usize| u32::from_be_bytes(header[index * 4..index * 4 + 4].try_into().unwrap());
+        if !(1..=VERSION).contains(&field(0)) {
+            return Err(invalid("unsupported strfile version"));
+        }
+        let count = field(1) as usize;
+
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+        if bytes.len() < (count + 1) * 4 {
+            return Err(invalid("truncated strfile index"));
+        }
+        let offsets = bytes
+            .chunks_exact(4)
+            .take(count + 1)
+            .map(|chunk| u32::from_be_bytes(chunk.try_into().unwrap()))
+            .collect();
+
+        Ok(Self {
+            longest: field(2),
+            shortest: field(3),
+            flags: Flags::from_bits(field(4)),
+            delimiter: header[20],
+            offsets,
+        })
+    }
+
+    pub fn write(&self, writer: &mut impl Write) -> io::Result<()> {
+        for field in [
+            VERSION,
+            self.len() as u32,
+            self.longest,
+            self.shortest,
+            self.flags.bits(),
+        ] {
+            writer.write_all(&field.to_be_bytes())?;
+        }
+        writer.write_all(&[self.delimiter, 0, 0, 0])?;
+        for offset in &self.offsets {
+            writer.write_all(&offset.to_be_bytes())?;
+        }
+        Ok(())
+    }
+}
+
+/// Where each non-empty fortune in `content` is, not counting the `delimiter` line after it.
+/// Lines may end in CRLF, and the last fortune needn't be followed by a delimiter.
+pub(crate) fn entries(content: &[u8], delimiter: u8) -> Vec<Range<usize>> {
+    let mut entries = Vec::new();
+    let mut start = 0;
+    let mut position = 0;
+    while position < content.len() {
+        let end = content[position..]
+            .iter()
+            .position(|&byte| byte == b'\n')
+            .map_or(content.len(), |newline| position + newline + 1);
+        if is_delimiter(&content[position..end], delimiter) {
+            if position > start {
+                entries.push(start..position);
+            }
+            start = end;
+        }
+        position = end;
+    }
+    if content.len() > start {
+        entries.push(start..content.len());
+    }
+    entries
+}
+
+pub(crate) fn is_delimiter(line: &[u8], delimiter: u8) -> bool {
+    matches!(line, [byte] | [byte, b'\n'] | [byte, b'\r', b'\n'] if *byte == delimiter)
+}
+
+/// A fortune's text from its bytes: with Unix line endings, no trailing newline, and decoded
+/// when the database is rot13-encoded.
+pub(crate) fn text(bytes: &[u8], rotated: bool) -> String {
+    let text = String::from_utf8_lossy(bytes).replace("\r\n", "\n");
+    let text = text.trim_end_matches('\n');
+    if rotated {
+        text.chars().map(rot13).collect()
+    } else {
+        text.to_string()
+    }
+}
+
+fn rot13(c: char) -> char {
+    match c {
+        'a'..='z' => (((c as u8 - b'a') + 13) % 26 + b'a') as char,
+        'A'..='Z' => (((c as u8 - b'A') + 13) % 26 + b'A') as char,
+        _ => c,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DATABASE: &[u8] = b"Hello\r\nthere\r\n%\r\n%\nSecond\n%\nThird is the longest\n%\n";
+
+    #[test]
+    fn finds_entries_across_line_endings() {
+        let entries: Vec<String> = entries(DATABASE, b'%')
+            .into_iter()
+            .map(|range| text(&DATABASE[range], false))
+            .collect();
+        assert_eq!(entries, ["Hello\nthere", "Second", "Third is the longest"]);
+    }
+
+    #[test]
+    fn round_trips_through_strfile_format() {
+        let index = Index::build(DATABASE, b'%', Flags::default()).unwrap();
+        assert_eq!(index.len(), 3);
+        assert_eq!(index.offsets, [0, 19, 28, DATABASE.len() as u32]);
+        assert_eq!((index.longest, index.shortest), (21, 7));
+
+        let mut bytes = Vec::new();
+        index.write(&mut bytes).unwrap();
+        assert_eq!(bytes.len(), HEADER_LEN + 4 * 4);
+        assert_eq!(&bytes[..8], [0, 0, 0, 2, 0, 0, 0, 3]);
+        assert_eq!(Index::read(&mut bytes.as_slice()).unwrap(), index);
+    }
+
+    #[test]
+    fn orders_entries() {
+        let flags = Flags {
+            ordered: true,
+            ..Flags::default()
+        };
+        let index = Index::build(b"beta\n%\nAlpha\n%\ngamma\n", b'%', flags).unwrap();
+        assert_eq!(index.offsets, [7, 0, 15, 21]);
+        assert_eq!(Flags::from_bits(flags.bits()), flags);
+    }
+
+    #[test]
+    fn decodes_rot13() {
+        assert_eq!(text(b"Uryyb, jbeyq!\n", true), "Hello, world!");
+    }
+}
@@ -1,14 +1,27 @@
+use rand::distributions::{Distribution, WeightedIndex};
 use rand::Rng;
 use thiserror::Error;
 
 use std::{
     error::Error,
-    fmt,
-    fs::File,
-    io::{self, Read},
-    path::Path,
+    fmt, fs, io,
+    path::{Path, PathBuf},
 };
 
+mod database;
+mod filter;
+pub mod index;
+mod search;
+mod selection;
+mod source;
+
+use database::Database;
+
+pub use filter::*;
+pub use search::*;
+pub use selection::*;
+pub use source::*;
+
 // Define our error types. These may be customized for our error handling cases.
 // Now we will be able to write our own errors, defer to an underlying error
 // implementation, or do something in between.
@@ -26,45 +39,243 @@ impl fmt::Display for NoFortunesError {
     }
 }
 
+/// Why databases couldn't be loaded, or a fortune chosen from them.
+#[derive(Debug, Error)]
+pub enum FortuneError {
+    #[error(transparent)]
+    NotFound(#[from] NotFoundError),
+    #[error("Failed to read {}", path.display())]
+    Read { path: PathBuf, source: io::Error },
+    #[error("Fortune percentages add up to {0}%, more than 100%")]
+    TooMuchPercent(f64),
+    #[error(transparent)]
+    NoFortunes(#[from] NoFortunesError),
+}
+
+/// Databases given together, like a directory's files, with the percentage of fortunes that
+/// should come from them, if any.
+#[derive(Debug)]
+struct Group {
+    percent: Option<f64>,
+    databases: Vec<Database>,
+}
+
 // from https://github.com/zuisong/rs-fortune
-pub struct Fortunes(Vec<String>);
+/// Fortunes from one or more databases, to choose from at random.
+#[derive(Debug)]
+pub struct Fortunes {
+    groups: Vec<Group>,
+    weighting: Weighting,
+}
+
 impl Fortunes {
     pub fn new(content: String) -> Result<Fortunes, Box<dyn Error>> {
-        let fortunes = content.split("\n%\n").map(|it| it.to_string()).collect();
-        Ok(Self(fortunes))
-    }
-
-    pub fn from_file(path: &String) -> Result<Fortunes, Box<dyn Error>> {
-        let file_path = Path::new(&path);
-        if !file_path.exists() {
-            return Err(io::Error::new(
-                io::ErrorKind::NotFound,
-                format!("The fortune file '{path}' does not exist"),
-            )
-            .into());
+        Ok(Self::single(Database::parse(content.as_bytes())))
+    }
+
+    fn single(database: Database) -> Self {
+        Self {
+            groups: vec![Group {
+                percent: None,
+                databases: vec![database],
+            }],
+            weighting: Weighting::default(),
         }
-        if file_path.is_dir() {
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidInput,
-                format!("'{path}' is a directory, not a file"),
-            )
-            .into());
+    }
+
+    /// Load the database at `path`, through its strfile index when it has one.
+    pub fn from_file(path: impl AsRef<Path>) -> io::Result<Fortunes> {
+        Ok(Self::single(Database::open(path.as_ref())?))
+    }
+
+    /// Load every database in `sources`, resolved in `search_path`, keeping only the fortunes
+    /// `options.filter` matches. A directory stands for all the databases in it, sharing its
+    /// percentage between them according to `options.weighting`.
+    ///
+    /// Filtering has to read every fortune, so it's as slow as a database without an index.
+    pub fn load(
+        sources: &[Source],
+        search_path: &SearchPath,
+        options: &Options,
+    ) -> Result<Fortunes, FortuneError> {
+        let percent: f64 = sources.iter().filter_map(|source| source.percent).sum();
+        if percent > 100.0 {
+            return Err(FortuneError::TooMuchPercent(percent));
+        }
+
+        let groups = sources
+            .iter()
+            .map(|source| {
+                let mut databases = Vec::new();
+                for path in search_path.resolve_all(&source.name, options.offensive)? {
+                    let files = database_files(&path).map_err(|source| FortuneError::Read {
+                        path: path.clone(),
+                        source,
+                    })?;
+                    for path in files {
+                        let database = Database::open(&path)
+                            .map_err(|source| FortuneError::Read { path, source })?;
+                        databases.push(match options.filter.is_empty() {
+                            true => database,
+                            false => database.filtered(&options.filter)?,
+                        });
+                    }
+                }
+                Ok(Group {
+                    percent: source.percent,
+                    databases,
+                })
+            })
+            .collect::<Result<_, FortuneError>>()?;
+
+        Ok(Self {
+            groups,
+            weighting: options.weighting,
+        })
+    }
+
+    /// How likely each database is to be chosen, in the order of `groups` and their
+    /// `databases`. Databases without a percentage share what the others leave over.
+    fn weights(&self) -> Vec<f64> {
+        let size = |database: &Database| match self.weighting {
+            Weighting::ByEntry => database.len() as f64,
+            Weighting::ByFile => (database.len() > 0) as u8 as f64,
+        };
+        let total = |groups: &mut dyn Iterator<Item = &Group>| -> f64 {
+            groups.flat_map(|group| &group.databases).map(size).sum()
+        };
+
+        let given: f64 = self.groups.iter().filter_map(|group| group.percent).sum();
+        let leftover = total(&mut self.groups.iter().filter(|group| group.percent.is_none()));
+
+        self.groups
+            .iter()
+            .flat_map(|group| {
+                let (percent, out_of) = match group.percent {
+                    Some(percent) => (percent, total(&mut std::iter::once(group))),
+                    None => (100.0 - given, leftover),
+                };
+                group.databases.iter().map(move |database| {
+                    if out_of > 0.0 {
+                        percent * size(database) / out_of
+                    } else {
+                        0.0
+                    }
+                })
+            })
+            .collect()
+    }
+
+    /// How many fortunes there are to choose from, in every database.
+    pub fn len(&self) -> usize {
+        self.groups
+            .iter()
+            .flat_map(|group| &group.databases)
+            .map(Database::len)
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn choose_one(&self) -> Result<String, FortuneError> {
+        self.choose_with(&mut rand::thread_rng())
+    }
+
+    /// Choose a database by its weight, then one of its fortunes, using `rng`.
+    pub fn choose_with(&self, rng: &mut impl Rng) -> Result<String, FortuneError> {
+        let databases: Vec<&Database> = self
+            .groups
+            .iter()
+            .flat_map(|group| &group.databases)
+            .collect();
+        let index = WeightedIndex::new(self.weights()).map_err(|_| NoFortunesError)?;
+        let database = databases[index.sample(rng)];
+        if database.len() == 0 {
+            return Err(NoFortunesError.into());
         }
-        let mut file = File::open(file_path)?;
-        let mut content = String::new();
-        file.read_to_string(&mut content)?;
 
-        Self::new(content)
+        database.get(rng.gen_range(0..database.len()))
+    }
+}
+
+/// The database files `path` stands for: itself, or the files in it when it's a directory.
+/// Files with an extension, like strfile's `.dat` indexes, and hidden files aren't databases.
+fn database_files(path: &Path) -> io::Result<Vec<PathBuf>> {
+    if !path.is_dir() {
+        return Ok(vec![path.to_path_buf()]);
     }
 
-    pub fn choose_one(&self) -> Result<&String, NoFortunesError> {
-        let fortunes = &self.0;
-        if fortunes.is_empty() {
-            return Err(NoFortunesError);
+    let mut files = Vec::new();
+    for entry in fs::read_dir(path)? {
+        let path = entry?.path();
+        let database = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| !name.starts_with('.') && !name.contains('.'));
+        if database && path.is_file() {
+            files.push(path);
         }
-        let mut rng = rand::thread_rng();
-        let index = rng.gen_range(0..fortunes.len());
+    }
+    files.sort();
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn fortunes(groups: &[(Option<f64>, &[usize])], weighting: Weighting) -> Fortunes {
+        let groups = groups
+            .iter()
+            .map(|(percent, sizes)| Group {
+                percent: *percent,
+                databases: sizes
+                    .iter()
+                    .map(|size| {
+                        Database::from_fortunes((0..*size).map(|index| index.to_string()).collect())
+                    })
+                    .collect(),
+            })
+            .collect();
+        Fortunes { groups, weighting }
+    }
+
+    #[test]
+    fn weighs_by_entry_or_by_file() {
+        let by_entry = fortunes(&[(None, &[30]), (None, &[10])], Weighting::ByEntry);
+        assert_eq!(by_entry.weights(), [75.0, 25.0]);
 
-        Ok(&fortunes[index])
+        let by_file = fortunes(&[(None, &[30]), (None, &[10])], Weighting::ByFile);
+        assert_eq!(by_file.weights(), [50.0, 50.0]);
+    }
+
+    #[test]
+    fn percentages_come_first() {
+        // Like `fortune 30% pickleisms 70% collected-quotes`
+        let given = fortunes(
+            &[(Some(30.0), &[10]), (Some(70.0), &[90])],
+            Weighting::ByEntry,
+        );
+        assert_eq!(given.weights(), [30.0, 70.0]);
+
+        // The rest share what's left, and a directory shares its percentage between its files
+        let mixed = fortunes(
+            &[(Some(40.0), &[1, 3]), (None, &[20]), (None, &[10])],
+            Weighting::ByEntry,
+        );
+        assert_eq!(mixed.weights(), [10.0, 30.0, 40.0, 20.0]);
+    }
+
+    #[test]
+    fn never_chooses_from_empty_databases() {
+        let fortunes = fortunes(&[(Some(50.0), &[0]), (None, &[2])], Weighting::ByFile);
+        let mut rng = StdRng::seed_from_u64(7);
+        for _ in 0..20 {
+            assert!(fortunes.choose_with(&mut rng).is_ok());
+        }
     }
 }
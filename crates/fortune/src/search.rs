@@ -0,0 +1,226 @@
+use segment::{Check, CheckStatus};
+use std::env;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+use crate::Offensive;
+
+/// Where packages install fortune databases: Debian and Ubuntu, Fedora and Arch, openSUSE,
+/// FreeBSD and Homebrew on Intel, Homebrew on Apple silicon, and Homebrew on Linux.
+const SYSTEM_DIRS: &[&str] = &[
+    "/usr/share/games/fortunes",
+    "/usr/share/fortune",
+    "/usr/share/games/fortune",
+    "/usr/local/share/games/fortunes",
+    "/opt/homebrew/share/games/fortunes",
+    "/home/linuxbrew/.linuxbrew/share/games/fortunes",
+];
+
+/// A database that isn't a file at the given path, or in any directory of the search path.
+#[derive(Debug, Error)]
+#[error("No fortune database `{name}`{}", list(searched))]
+pub struct NotFoundError {
+    pub name: String,
+    pub searched: Vec<PathBuf>,
+}
+
+/// Where the database was looked for, if it wasn't given as a path.
+fn list(dirs: &[PathBuf]) -> String {
+    if dirs.is_empty() {
+        return String::new();
+    }
+    let dirs: Vec<String> = dirs.iter().map(|dir| dir.display().to_string()).collect();
+    format!(" in {}", dirs.join(", "))
+}
+
+/// Directories to look in for fortune databases given by name, like `intro`, in order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchPath(Vec<PathBuf>);
+
+impl SearchPath {
+    pub fn new(dirs: Vec<PathBuf>) -> Self {
+        Self(dirs)
+    }
+
+    /// Every directory in `$FORTUNE_PATH`, then the user's `$XDG_DATA_HOME/fortunes` (or
+    /// `~/.local/share/fortunes`), then where packages install them on each platform.
+    pub fn from_env() -> Self {
+        let mut dirs: Vec<PathBuf> = env::var_os("FORTUNE_PATH")
+            .map(|path| env::split_paths(&path).collect())
+            .unwrap_or_default();
+        let data_home = env::var_os("XDG_DATA_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")));
+        dirs.extend(data_home.map(|dir| dir.join("fortunes")));
+        dirs.extend(SYSTEM_DIRS.iter().map(PathBuf::from));
+        dirs.retain(|dir| !dir.as_os_str().is_empty());
+        Self(dirs)
+    }
+
+    /// Look in `dirs` before any of the others.
+    pub fn with_dirs_first(mut self, dirs: impl IntoIterator<Item = PathBuf>) -> Self {
+        let mut first: Vec<PathBuf> = dirs.into_iter().collect();
+        first.append(&mut self.0);
+        self.0 = first;
+        self
+    }
+
+    pub fn dirs(&self) -> &[PathBuf] {
+        &self.0
+    }
+
+    /// The database `name` refers to: itself when it's a path, like `./quotes` or
+    /// `/usr/share/games/fortunes/wisdom`, or else the first file or directory called `name` in
+    /// one of the directories.
+    pub fn resolve(&self, name: &str) -> Result<PathBuf, NotFoundError> {
+        self.find(name, |path| path.to_path_buf())
+    }
+
+    /// Like [`SearchPath::resolve`], but for the offensive database of that name, which fortune
+    /// keeps in an `off` directory next to the others: `off/<name>` in one of the directories,
+    /// or `<dir>/off` for a directory of databases.
+    pub fn resolve_offensive(&self, name: &str) -> Result<PathBuf, NotFoundError> {
+        self.find(name, |path| match (path.parent(), path.file_name()) {
+            (Some(parent), Some(file_name)) if !path.is_dir() => parent.join("off").join(file_name),
+            _ => path.join("off"),
+        })
+    }
+
+    /// The databases `name` refers to, with or without their offensive counterparts. With
+    /// [`Offensive::Also`], it's enough for either of them to exist.
+    pub fn resolve_all(
+        &self,
+        name: &str,
+        offensive: Offensive,
+    ) -> Result<Vec<PathBuf>, NotFoundError> {
+        match offensive {
+            Offensive::Never => Ok(vec![self.resolve(name)?]),
+            Offensive::Only => Ok(vec![self.resolve_offensive(name)?]),
+            Offensive::Also => match (self.resolve(name), self.resolve_offensive(name).ok()) {
+                (Ok(path), offensive) => {
+                    Ok([Some(path), offensive].into_iter().flatten().collect())
+                }
+                (Err(_), Some(offensive)) => Ok(vec![offensive]),
+                (Err(error), None) => Err(error),
+            },
+        }
+    }
+
+    /// Preflight checks that the databases `name` refers to can be read, for the segment
+    /// configured under `[segment.<section>]`.
+    pub fn checks(&self, name: &str, offensive: Offensive, section: &str) -> Vec<Check> {
+        let checks = match self.resolve_all(name, offensive) {
+            Ok(paths) => paths.into_iter().map(Check::readable).collect(),
+            Err(_) => vec![Check::new(name, CheckStatus::Missing)],
+        };
+        let hint = format!(
+            "Install fortune (`brew install fortune` or `apt install fortune-mod`) or set `fortune` or `fortune_path` under [segment.{section}]"
+        );
+        checks
+            .into_iter()
+            .map(|check| check.with_hint(hint.clone()))
+            .collect()
+    }
+
+    /// The first of the places `name` could be that exists, after `locate` maps each of them.
+    fn find(
+        &self,
+        name: &str,
+        locate: impl Fn(&Path) -> PathBuf,
+    ) -> Result<PathBuf, NotFoundError> {
+        let path = Path::new(name);
+        let (candidates, searched) = if path.components().count() > 1 || path.is_absolute() {
+            (vec![locate(path)], Vec::new())
+        } else {
+            let candidates = self.0.iter().map(|dir| locate(&dir.join(name))).collect();
+            (candidates, self.0.clone())
+        };
+
+        candidates
+            .into_iter()
+            .find(|candidate| candidate.exists())
+            .ok_or_else(|| NotFoundError {
+                name: name.to_string(),
+                searched,
+            })
+    }
+}
+
+impl Default for SearchPath {
+    fn default() -> Self {
+        Self::from_env()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn temporary_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("fortune-{name}-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn resolves_names_in_order() {
+        let first = temporary_dir("first");
+        let second = temporary_dir("second");
+        fs::write(second.join("intro"), "Hello\n%\nWelcome\n").unwrap();
+        fs::write(first.join("wisdom"), "Be kind\n").unwrap();
+        fs::write(second.join("wisdom"), "Be brief\n").unwrap();
+
+        let search_path = SearchPath::new(vec![first.clone(), second.clone()]);
+        assert_eq!(search_path.resolve("intro").unwrap(), second.join("intro"));
+        assert_eq!(search_path.resolve("wisdom").unwrap(), first.join("wisdom"));
+
+        let error = search_path.resolve("zippy").unwrap_err();
+        assert_eq!(error.searched, [first.clone(), second.clone()]);
+
+        fs::create_dir_all(second.join("off")).unwrap();
+        fs::write(second.join("off/wisdom"), "Ornirf\n").unwrap();
+        assert_eq!(
+            search_path.resolve_offensive("wisdom").unwrap(),
+            second.join("off/wisdom")
+        );
+        assert!(search_path.resolve_offensive("intro").is_err());
+
+        fs::remove_dir_all(first).unwrap();
+        fs::remove_dir_all(second).unwrap();
+    }
+
+    #[test]
+    fn checks_name_the_segment() {
+        let dir = temporary_dir("checks");
+        fs::write(dir.join("intro"), "Hello\n").unwrap();
+        let search_path = SearchPath::new(vec![dir.clone()]);
+
+        let checks = search_path.checks("intro", Offensive::Never, "quote");
+        assert_eq!(checks.len(), 1);
+        assert!(checks[0].passed());
+
+        let checks = search_path.checks("zippy", Offensive::Also, "heading");
+        assert_eq!(checks[0].status, CheckStatus::Missing);
+        assert!(checks[0]
+            .hint
+            .as_deref()
+            .is_some_and(|hint| hint.ends_with("under [segment.heading]")));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn paths_resolve_to_themselves() {
+        let dir = temporary_dir("paths");
+        let path = dir.join("quotes");
+        fs::write(&path, "Hello\n").unwrap();
+
+        let search_path = SearchPath::new(Vec::new());
+        assert_eq!(search_path.resolve(path.to_str().unwrap()).unwrap(), path);
+        assert!(search_path.resolve("quotes").is_err());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,214 @@
+use chrono::Local;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs;
+use std::io;
+use std::path::Path;
+use tracing::warn;
+
+use crate::{FortuneError, Fortunes};
+
+/// How many recently shown fortunes [`History`] remembers at most.
+const HISTORY_LENGTH: usize = 64;
+
+/// How many times to draw again when a fortune was shown recently, before showing it anyway.
+const ATTEMPTS: usize = 32;
+
+/// How to choose a fortune each time one is shown.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Selection {
+    /// A different one at random every time.
+    #[default]
+    Random,
+    /// The same one all day on this host, in every pane and for everyone who logs in to it.
+    Daily,
+    /// At random, but none of the last ones shown, as remembered in a [`History`].
+    NoRepeat,
+}
+
+impl Selection {
+    /// The generator to choose with: from `seed` when there is one, so the output can be
+    /// reproduced, or else from today's date and the host's name for [`Selection::Daily`].
+    pub fn rng(self, seed: Option<u64>) -> StdRng {
+        match (seed, self) {
+            (Some(seed), _) => StdRng::seed_from_u64(seed),
+            (None, Selection::Daily) => {
+                let host = hostname::get().unwrap_or_default();
+                let day = Local::now().date_naive().to_string();
+                StdRng::seed_from_u64(fingerprint(&format!("{day} {}", host.to_string_lossy())))
+            }
+            (None, _) => StdRng::from_entropy(),
+        }
+    }
+}
+
+/// The fortunes shown most recently, oldest first, by fingerprint.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct History {
+    recent: VecDeque<u64>,
+}
+
+impl History {
+    /// Read the history at `path`. A missing or unreadable one only costs its memory, so it's
+    /// treated as empty.
+    pub fn load(path: &Path) -> Self {
+        fs::read(path)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .unwrap_or_default()
+    }
+
+    /// Replace the history at `path`, through a temporary file so a concurrent login never
+    /// reads half of it.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory)?;
+        }
+        let temporary = path.with_extension(format!("json.{}", std::process::id()));
+        fs::write(&temporary, serde_json::to_vec(self)?)?;
+        fs::rename(&temporary, path)
+    }
+
+    pub fn contains(&self, fortune: &str) -> bool {
+        self.recent.contains(&fingerprint(fortune))
+    }
+
+    /// Remember `fortune`, forgetting the oldest ones beyond `limit`.
+    fn record(&mut self, fortune: &str, limit: usize) {
+        let fingerprint = fingerprint(fortune);
+        self.recent.retain(|recent| *recent != fingerprint);
+        self.recent.push_back(fingerprint);
+        while self.recent.len() > limit.max(1) {
+            self.recent.pop_front();
+        }
+    }
+}
+
+impl Fortunes {
+    /// Choose a fortune the way `selection` says, from `seed` when there is one. For
+    /// [`Selection::NoRepeat`] the [`History`] at `history` is read and updated, except with a
+    /// seed: that's for reproducing output, which remembering what was shown would defeat.
+    pub fn choose(
+        &self,
+        selection: Selection,
+        seed: Option<u64>,
+        history: Option<&Path>,
+    ) -> Result<String, FortuneError> {
+        let mut rng = selection.rng(seed);
+        let path = match (selection, seed, history) {
+            (Selection::NoRepeat, None, Some(path)) => path,
+            _ => return self.choose_with(&mut rng),
+        };
+
+        let mut recent = History::load(path);
+        let fortune = self.choose_new(&mut rng, &mut recent)?;
+        if let Err(error) = recent.save(path) {
+            warn!("Failed to write {}: {error}", path.display());
+        }
+        Ok(fortune)
+    }
+
+    /// Like [`Fortunes::choose_with`], but draw again while the fortune is in `history`, then
+    /// record the one chosen there.
+    ///
+    /// At most half of all fortunes are remembered, so a new one is usually found quickly; one
+    /// that was shown recently still comes up when a few draws in a row turn up nothing new.
+    pub fn choose_new(
+        &self,
+        rng: &mut impl Rng,
+        history: &mut History,
+    ) -> Result<String, FortuneError> {
+        let mut fortune = self.choose_with(rng)?;
+        for _ in 1..ATTEMPTS {
+            if !history.contains(&fortune) {
+                break;
+            }
+            fortune = self.choose_with(rng)?;
+        }
+
+        history.record(&fortune, (self.len() / 2).min(HISTORY_LENGTH));
+        Ok(fortune)
+    }
+}
+
+/// FNV-1a, which unlike std's hasher is the same in every build, so the daily fortune and
+/// saved histories don't change with the compiler.
+fn fingerprint(text: &str) -> u64 {
+    text.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seeds_reproduce_choices() {
+        let fortunes = Fortunes::new("a\n%\nb\n%\nc\n%\nd\n%\ne\n".to_string()).unwrap();
+        let choices = |seed| {
+            let mut rng = Selection::Random.rng(Some(seed));
+            (0..10)
+                .map(|_| fortunes.choose_with(&mut rng).unwrap())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(choices(42), choices(42));
+    }
+
+    #[test]
+    fn no_repeat_skips_recent_fortunes() {
+        let fortunes = Fortunes::new("a\n%\nb\n%\nc\n%\nd\n".to_string()).unwrap();
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut history = History::default();
+
+        // Half of the four are remembered, so each one differs from the two before it
+        let chosen: Vec<String> = (0..20)
+            .map(|_| fortunes.choose_new(&mut rng, &mut history).unwrap())
+            .collect();
+        for window in chosen.windows(3) {
+            assert_ne!(window[2], window[1]);
+            assert_ne!(window[2], window[0]);
+        }
+        assert_eq!(history.recent.len(), 2);
+    }
+
+    #[test]
+    fn seeds_leave_history_alone() {
+        let fortunes = Fortunes::new("a\n%\nb\n%\nc\n%\nd\n".to_string()).unwrap();
+        let path = std::env::temp_dir()
+            .join(format!("fortune-seeded-{}", std::process::id()))
+            .join("history.json");
+
+        fortunes
+            .choose(Selection::NoRepeat, Some(3), Some(&path))
+            .unwrap();
+        assert!(!path.exists());
+
+        fortunes
+            .choose(Selection::NoRepeat, None, Some(&path))
+            .unwrap();
+        assert_eq!(History::load(&path).recent.len(), 1);
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn history_survives_saving() {
+        let path = std::env::temp_dir()
+            .join(format!("fortune-history-{}", std::process::id()))
+            .join("history.json");
+        let mut history = History::default();
+        history.record("Carpe diem.", 8);
+        history.save(&path).unwrap();
+
+        let loaded = History::load(&path);
+        assert!(loaded.contains("Carpe diem."));
+        assert_eq!(loaded, history);
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+        assert_eq!(History::load(&path), History::default());
+    }
+}
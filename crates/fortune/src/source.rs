@@ -0,0 +1,186 @@
+use serde::de::{self, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer};
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+
+/// A database to choose fortunes from, optionally with the percentage of fortunes that should
+/// come from it, written like BSD fortune's arguments: `30% pickleisms` or `collected-quotes`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct Source {
+    /// The database's name or path, as resolved by [`SearchPath::resolve`](crate::SearchPath::resolve).
+    pub name: String,
+    pub percent: Option<f64>,
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum SourceError {
+    #[error("Invalid percentage in `{0}`")]
+    InvalidPercent(String),
+    #[error("Missing database name in `{0}`")]
+    MissingName(String),
+}
+
+impl FromStr for Source {
+    type Err = SourceError;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        let source = source.trim();
+        let Some((percent, name)) = source
+            .split_once('%')
+            .and_then(|(percent, name)| Some((percent.trim_end().parse::<f64>().ok()?, name)))
+        else {
+            return Ok(Self {
+                name: source.to_string(),
+                percent: None,
+            });
+        };
+
+        if !(0.0..=100.0).contains(&percent) {
+            return Err(SourceError::InvalidPercent(source.to_string()));
+        }
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(SourceError::MissingName(source.to_string()));
+        }
+        Ok(Self {
+            name: name.to_string(),
+            percent: Some(percent),
+        })
+    }
+}
+
+impl TryFrom<String> for Source {
+    type Error = SourceError;
+
+    fn try_from(source: String) -> Result<Self, Self::Error> {
+        source.parse()
+    }
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.percent {
+            Some(percent) => write!(f, "{percent}% {}", self.name),
+            None => f.write_str(&self.name),
+        }
+    }
+}
+
+/// One [`Source`] or a list of them, so configuration can say `fortune = "intro"` as well as
+/// `fortune = ["30% pickleisms", "70% collected-quotes"]`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sources(pub Vec<Source>);
+
+// Written out rather than `#[serde(untagged)]`, which would replace why a source is invalid
+// with "data did not match any variant".
+impl<'de> Deserialize<'de> for Sources {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct SourcesVisitor;
+
+        impl<'de> Visitor<'de> for SourcesVisitor {
+            type Value = Sources;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a fortune database or a list of them")
+            }
+
+            fn visit_str<E: de::Error>(self, source: &str) -> Result<Sources, E> {
+                let source = source.parse().map_err(E::custom)?;
+                Ok(Sources(vec![source]))
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Sources, A::Error> {
+                let mut sources = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+                while let Some(source) = seq.next_element()? {
+                    sources.push(source);
+                }
+                Ok(Sources(sources))
+            }
+        }
+
+        deserializer.deserialize_any(SourcesVisitor)
+    }
+}
+
+/// How to choose between databases that weren't given a percentage.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Weighting {
+    /// Every fortune is as likely as any other, so bigger databases come up more often.
+    #[default]
+    ByEntry,
+    /// Every database is as likely as any other, however many fortunes it has, like BSD
+    /// fortune's `-e`.
+    ByFile,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_percentages() {
+        let source: Source = "30% pickleisms".parse().unwrap();
+        assert_eq!(source.name, "pickleisms");
+        assert_eq!(source.percent, Some(30.0));
+
+        let source: Source = "30 % pickleisms".parse().unwrap();
+        assert_eq!(source.percent, Some(30.0));
+
+        let source: Source = "collected-quotes".parse().unwrap();
+        assert_eq!(source.percent, None);
+
+        // Only a leading percentage counts, not one in a path
+        let source: Source = "/srv/100% true".parse().unwrap();
+        assert_eq!(source.name, "/srv/100% true");
+
+        assert!(matches!(
+            "120% hype".parse::<Source>(),
+            Err(SourceError::InvalidPercent(_))
+        ));
+        assert!(matches!(
+            "50%".parse::<Source>(),
+            Err(SourceError::MissingName(_))
+        ));
+    }
+
+    #[test]
+    fn one_or_many_sources() {
+        let one: Sources = serde_json::from_str(r#""intro""#).unwrap();
+        assert_eq!(one.0, ["intro".parse().unwrap()]);
+
+        let many: Sources =
+            serde_json::from_str(r#"["30% pickleisms", "70% collected-quotes"]"#).unwrap();
+        assert_eq!(many.0.len(), 2);
+        assert_eq!(many.0[1].percent, Some(70.0));
+    }
+
+    #[test]
+    fn invalid_sources_say_why() {
+        let error = serde_json::from_str::<Sources>(r#""130% intro""#).unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .starts_with("Invalid percentage in `130% intro`"),
+            "{error}"
+        );
+
+        let error = serde_json::from_str::<Sources>(r#"["intro", "30 %"]"#).unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .starts_with("Missing database name in `30 %`"),
+            "{error}"
+        );
+
+        let error = serde_json::from_str::<Sources>("30").unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .contains("a fortune database or a list of them"),
+            "{error}"
+        );
+    }
+}
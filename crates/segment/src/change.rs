@@ -0,0 +1,66 @@
+use ratatui::style::Style;
+use serde::{Deserialize, Serialize};
+
+use crate::Theme;
+
+/// Something that's different about a segment since it was last shown, like "/ grew 12 GB" or
+/// "container api exited", for the `changes` segment.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Change {
+    pub text: String,
+    pub severity: Severity,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Severity {
+    /// Worth knowing, like new updates.
+    #[default]
+    Notice,
+    /// Worth a look, like a disk past its warning threshold.
+    Warning,
+    /// Probably broken, like a container that exited with an error.
+    Critical,
+}
+
+impl Change {
+    pub fn notice(text: impl Into<String>) -> Self {
+        Self::new(text, Severity::Notice)
+    }
+
+    pub fn warning(text: impl Into<String>) -> Self {
+        Self::new(text, Severity::Warning)
+    }
+
+    pub fn critical(text: impl Into<String>) -> Self {
+        Self::new(text, Severity::Critical)
+    }
+
+    pub fn new(text: impl Into<String>, severity: Severity) -> Self {
+        Self {
+            text: text.into(),
+            severity,
+        }
+    }
+}
+
+impl Severity {
+    /// How bad `value` is: critical from `critical` on, a warning from `warning` on.
+    pub fn from_thresholds(value: f64, warning: f64, critical: f64) -> Self {
+        if value >= critical {
+            Severity::Critical
+        } else if value >= warning {
+            Severity::Warning
+        } else {
+            Severity::Notice
+        }
+    }
+
+    pub fn style(self, theme: &Theme) -> Style {
+        match self {
+            Severity::Notice => Style::default(),
+            Severity::Warning => theme.warn,
+            Severity::Critical => theme.critical,
+        }
+    }
+}
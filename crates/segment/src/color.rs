@@ -0,0 +1,212 @@
+use ratatui::buffer::Buffer;
+use ratatui::style::Color;
+
+/// How many colors the output can show; anything richer is mapped to the closest it has.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ColorDepth {
+    /// No colors, only modifiers like bold and dim.
+    None,
+    /// The 16 standard ANSI colors, e.g. the Linux console or a serial line.
+    Ansi16,
+    /// The xterm 256-color palette, e.g. Terminal.app or tmux without RGB.
+    Ansi256,
+    /// 24-bit RGB.
+    TrueColor,
+}
+
+/// The 16 ANSI colors with xterm's RGB values, for finding the closest one.
+const ANSI16: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::Red, (205, 0, 0)),
+    (Color::Green, (0, 205, 0)),
+    (Color::Yellow, (205, 205, 0)),
+    (Color::Blue, (0, 0, 238)),
+    (Color::Magenta, (205, 0, 205)),
+    (Color::Cyan, (0, 205, 205)),
+    (Color::Gray, (229, 229, 229)),
+    (Color::DarkGray, (127, 127, 127)),
+    (Color::LightRed, (255, 0, 0)),
+    (Color::LightGreen, (0, 255, 0)),
+    (Color::LightYellow, (255, 255, 0)),
+    (Color::LightBlue, (92, 92, 255)),
+    (Color::LightMagenta, (255, 0, 255)),
+    (Color::LightCyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+
+/// Levels of each channel in the 6×6×6 cube of the 256-color palette.
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+impl ColorDepth {
+    /// Work out the depth from the environment: `NO_COLOR` turns colors off, `COLORTERM`
+    /// announces 24-bit support, and `TERM` tells 256-color terminals from the rest.
+    /// `CLICOLOR_FORCE` keeps at least the 16 colors even when `TERM` is `dumb`.
+    pub fn detect() -> Self {
+        Self::from_env(|name| std::env::var(name).ok())
+    }
+
+    pub fn from_env(var: impl Fn(&str) -> Option<String>) -> Self {
+        // https://no-color.org: any non-empty value
+        if var("NO_COLOR").is_some_and(|value| !value.is_empty()) {
+            return ColorDepth::None;
+        }
+
+        if var("COLORTERM").is_some_and(|value| value == "truecolor" || value == "24bit") {
+            return ColorDepth::TrueColor;
+        }
+
+        // https://bixense.com/clicolors: any non-empty value but 0
+        let forced = var("CLICOLOR_FORCE").is_some_and(|value| !value.is_empty() && value != "0");
+
+        match var("TERM").as_deref() {
+            Some("dumb") if !forced => ColorDepth::None,
+            Some(term) if term.contains("truecolor") || term.contains("direct") => {
+                ColorDepth::TrueColor
+            }
+            Some(term) if term.contains("256color") => ColorDepth::Ansi256,
+            _ => ColorDepth::Ansi16,
+        }
+    }
+
+    /// The closest color to `color` this depth can show.
+    pub fn downgrade(self, color: Color) -> Color {
+        match (self, color) {
+            (_, Color::Reset) => Color::Reset,
+            (ColorDepth::None, _) => Color::Reset,
+            (ColorDepth::TrueColor, color) => color,
+            (ColorDepth::Ansi256, Color::Rgb(r, g, b)) => Color::Indexed(to_ansi256(r, g, b)),
+            (ColorDepth::Ansi256, color) => color,
+            (ColorDepth::Ansi16, Color::Rgb(r, g, b)) => closest_ansi16((r, g, b)),
+            (ColorDepth::Ansi16, Color::Indexed(index)) => closest_ansi16(indexed_rgb(index)),
+            (ColorDepth::Ansi16, color) => color,
+        }
+    }
+
+    /// Downgrade the colors of every cell in `buffer`.
+    pub fn apply(self, buffer: &mut Buffer) {
+        if self == ColorDepth::TrueColor {
+            return;
+        }
+        for cell in buffer.content.iter_mut() {
+            cell.fg = self.downgrade(cell.fg);
+            cell.bg = self.downgrade(cell.bg);
+        }
+    }
+}
+
+fn to_ansi256(r: u8, g: u8, b: u8) -> u8 {
+    // Grays have a finer ramp of their own
+    if r == g && g == b {
+        return match r {
+            0..=3 => 16,
+            248..=255 => 231,
+            _ => 232 + ((r as u16 - 8) / 10).min(23) as u8,
+        };
+    }
+
+    let level = |value: u8| {
+        CUBE_LEVELS
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, level)| (**level as i16 - value as i16).abs())
+            .map_or(0, |(index, _)| index as u8)
+    };
+    16 + 36 * level(r) + 6 * level(g) + level(b)
+}
+
+/// RGB for an entry of the 256-color palette.
+fn indexed_rgb(index: u8) -> (u8, u8, u8) {
+    match index {
+        0..=15 => ANSI16[index as usize].1,
+        16..=231 => {
+            let index = index - 16;
+            (
+                CUBE_LEVELS[(index / 36) as usize],
+                CUBE_LEVELS[(index / 6 % 6) as usize],
+                CUBE_LEVELS[(index % 6) as usize],
+            )
+        }
+        _ => {
+            let gray = 8 + (index - 232) * 10;
+            (gray, gray, gray)
+        }
+    }
+}
+
+fn closest_ansi16((r, g, b): (u8, u8, u8)) -> Color {
+    let distance = |(r2, g2, b2): (u8, u8, u8)| {
+        let dr = r as i32 - r2 as i32;
+        let dg = g as i32 - g2 as i32;
+        let db = b as i32 - b2 as i32;
+        dr * dr + dg * dg + db * db
+    };
+    ANSI16
+        .iter()
+        .min_by_key(|(_, rgb)| distance(*rgb))
+        .map_or(Color::Reset, |(color, _)| *color)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env<'a>(vars: &'a [(&str, &str)]) -> impl Fn(&str) -> Option<String> + 'a {
+        move |name| {
+            vars.iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| value.to_string())
+        }
+    }
+
+    #[test]
+    fn detects_depth_from_environment() {
+        let detect = |vars: &[(&str, &str)]| ColorDepth::from_env(env(vars));
+
+        assert_eq!(detect(&[("TERM", "xterm-256color")]), ColorDepth::Ansi256);
+        assert_eq!(
+            detect(&[("TERM", "xterm-256color"), ("COLORTERM", "truecolor")]),
+            ColorDepth::TrueColor
+        );
+        assert_eq!(detect(&[("TERM", "vt220")]), ColorDepth::Ansi16);
+        assert_eq!(detect(&[("TERM", "dumb")]), ColorDepth::None);
+        assert_eq!(
+            detect(&[("TERM", "dumb"), ("CLICOLOR_FORCE", "1")]),
+            ColorDepth::Ansi16
+        );
+        assert_eq!(
+            detect(&[("TERM", "dumb"), ("CLICOLOR_FORCE", "0")]),
+            ColorDepth::None
+        );
+        assert_eq!(
+            detect(&[("TERM", "xterm-256color"), ("CLICOLOR_FORCE", "1")]),
+            ColorDepth::Ansi256
+        );
+        assert_eq!(
+            detect(&[("NO_COLOR", "1"), ("COLORTERM", "truecolor")]),
+            ColorDepth::None
+        );
+        assert_eq!(
+            detect(&[("NO_COLOR", ""), ("TERM", "screen-256color")]),
+            ColorDepth::Ansi256
+        );
+    }
+
+    #[test]
+    fn downgrades_rgb() {
+        let orange = Color::Rgb(0xcb, 0x4b, 0x16);
+
+        assert_eq!(ColorDepth::TrueColor.downgrade(orange), orange);
+        assert_eq!(ColorDepth::Ansi256.downgrade(orange), Color::Indexed(166));
+        assert_eq!(
+            ColorDepth::Ansi256.downgrade(Color::Rgb(128, 128, 128)),
+            Color::Indexed(244)
+        );
+        assert_eq!(ColorDepth::Ansi16.downgrade(orange), Color::Red);
+        assert_eq!(
+            ColorDepth::Ansi16.downgrade(Color::Indexed(21)),
+            Color::Blue
+        );
+        assert_eq!(ColorDepth::None.downgrade(orange), Color::Reset);
+        assert_eq!(ColorDepth::Ansi16.downgrade(Color::Green), Color::Green);
+    }
+}
@@ -0,0 +1,99 @@
+//! Helpers for fitting a segment's content into however many columns it's given.
+
+use ratatui::text::{Line, Span};
+use ratatui::widgets::Paragraph;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// Rows `paragraph` takes up in an area `width` columns wide, following its wrapping and
+/// including its block's top and bottom padding. Subtract any left and right padding from
+/// `width` first.
+pub fn paragraph_height(paragraph: &Paragraph, width: u16) -> u16 {
+    paragraph.line_count(width).try_into().unwrap_or(u16::MAX)
+}
+
+/// Shorten `text` to at most `width` columns, ending it with "…" when anything was cut.
+pub fn truncate(text: &str, width: usize) -> String {
+    if text.width() <= width {
+        return text.to_string();
+    }
+    if width == 0 {
+        return String::new();
+    }
+
+    let mut truncated = String::new();
+    let mut used = 0;
+    for char in text.chars() {
+        let char_width = char.width().unwrap_or(0);
+        if used + char_width > width - 1 {
+            break;
+        }
+        truncated.push(char);
+        used += char_width;
+    }
+    truncated.push('…');
+    truncated
+}
+
+/// Lay `items` out on as few lines `width` columns wide as they fit on, in order, with
+/// `separator` between them. An item never breaks across lines; one too wide for a line of its
+/// own is left for the area to clip.
+pub fn flow<'a>(items: Vec<Vec<Span<'a>>>, separator: &'a str, width: u16) -> Vec<Line<'a>> {
+    let width = width as usize;
+    let separator_width = separator.width();
+    let line_end = separator.trim_end();
+    let mut lines = Vec::new();
+    let mut line: Vec<Span> = Vec::new();
+    let mut used = 0;
+
+    for item in items {
+        let item_width: usize = item.iter().map(Span::width).sum();
+        if !line.is_empty() {
+            if used + separator_width + item_width <= width {
+                line.push(Span::raw(separator));
+                used += separator_width;
+            } else {
+                line.push(Span::raw(line_end));
+                lines.push(Line::from(std::mem::take(&mut line)));
+                used = 0;
+            }
+        }
+        used += item_width;
+        line.extend(item);
+    }
+
+    if !line.is_empty() {
+        lines.push(Line::from(line));
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn truncates_with_ellipsis() {
+        assert_eq!(truncate("postgres", 10), "postgres");
+        assert_eq!(truncate("postgres", 8), "postgres");
+        assert_eq!(truncate("postgres", 5), "post…");
+        assert_eq!(truncate("postgres", 0), "");
+    }
+
+    #[test]
+    fn flows_items_onto_lines() {
+        let items = ["CPU 45.0°C", "GPU 50.0°C", "NVMe 38.0°C"]
+            .into_iter()
+            .map(|item| vec![Span::raw(item)])
+            .collect::<Vec<_>>();
+
+        let lines = flow(items.clone(), ", ", 80);
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0].to_string(), "CPU 45.0°C, GPU 50.0°C, NVMe 38.0°C");
+
+        let lines = flow(items, ", ", 24);
+        assert_eq!(
+            lines.iter().map(Line::to_string).collect::<Vec<_>>(),
+            ["CPU 45.0°C, GPU 50.0°C,", "NVMe 38.0°C"]
+        );
+    }
+}
@@ -0,0 +1,208 @@
+use ratatui::layout::Rect;
+use serde::Deserialize;
+
+/// How segments flow into columns on wide terminals, from the configuration's `[layout]` table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LayoutConfig {
+    /// Most columns to use; `None` uses as many as fit.
+    pub columns: Option<u16>,
+    /// Narrowest a column may get; narrower terminals get fewer columns, down to one.
+    pub column_width: u16,
+    /// Blank columns between columns.
+    pub gap: u16,
+}
+
+impl Default for LayoutConfig {
+    fn default() -> Self {
+        Self {
+            columns: None,
+            column_width: 60,
+            gap: 2,
+        }
+    }
+}
+
+impl LayoutConfig {
+    /// How many columns fit in `width`, always at least one.
+    pub fn column_count(&self, width: u16) -> u16 {
+        let fit =
+            width.saturating_add(self.gap) / self.column_width.saturating_add(self.gap).max(1);
+        fit.clamp(1, self.columns.unwrap_or(u16::MAX).max(1))
+    }
+
+    /// Width of each of `count` columns across `width`.
+    pub fn column_width(&self, width: u16, count: u16) -> u16 {
+        let count = count.max(1);
+        width.saturating_sub(self.gap.saturating_mul(count - 1)) / count
+    }
+}
+
+/// A segment to place: how tall it is, and whether it spans every column like the heading.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LayoutItem {
+    pub height: u16,
+    pub full_width: bool,
+}
+
+/// Place `items` in `area`, top to bottom. Full-width items take a row of their own; runs of
+/// items between them are flowed down `columns` columns in order, balanced so the tallest
+/// column is as short as possible.
+///
+/// Returns an area for each item, and the total height used.
+pub fn arrange(
+    items: &[LayoutItem],
+    area: Rect,
+    columns: u16,
+    config: &LayoutConfig,
+) -> (Vec<Rect>, u16) {
+    let columns = columns.max(1);
+    let column_width = config.column_width(area.width, columns);
+
+    let mut areas = Vec::with_capacity(items.len());
+    let mut y = area.y;
+    let mut start = 0;
+    while start < items.len() {
+        if items[start].full_width {
+            let height = items[start].height;
+            areas.push(Rect::new(area.x, y, area.width, height));
+            y += height;
+            start += 1;
+            continue;
+        }
+
+        let end = start
+            + items[start..]
+                .iter()
+                .take_while(|item| !item.full_width)
+                .count();
+        let heights: Vec<u16> = items[start..end].iter().map(|item| item.height).collect();
+        let limit = balanced_height(&heights, columns);
+
+        let mut column: u16 = 0;
+        let mut column_y = y;
+        let mut tallest = 0;
+        for &height in &heights {
+            if column_y > y && column_y - y + height > limit {
+                column += 1;
+                column_y = y;
+            }
+            let x = area
+                .x
+                .saturating_add(column.saturating_mul(column_width.saturating_add(config.gap)));
+            areas.push(Rect::new(x, column_y, column_width, height));
+            column_y += height;
+            tallest = tallest.max(column_y - y);
+        }
+
+        y += tallest;
+        start = end;
+    }
+
+    (areas, y - area.y)
+}
+
+/// The smallest column height that fits `heights`, in order, into at most `columns` columns.
+fn balanced_height(heights: &[u16], columns: u16) -> u16 {
+    let fits = |limit: u16| {
+        let mut used = 1;
+        let mut column_height = 0;
+        for &height in heights {
+            if column_height > 0 && column_height + height > limit {
+                used += 1;
+                column_height = 0;
+            }
+            column_height += height;
+        }
+        used <= columns
+    };
+
+    let mut low = heights.iter().copied().max().unwrap_or(0);
+    let mut high = heights.iter().sum();
+    while low < high {
+        let middle = low + (high - low) / 2;
+        if fits(middle) {
+            high = middle;
+        } else {
+            low = middle + 1;
+        }
+    }
+    low
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(height: u16) -> LayoutItem {
+        LayoutItem {
+            height,
+            full_width: false,
+        }
+    }
+
+    #[test]
+    fn column_count_falls_back_to_one() {
+        let config = LayoutConfig::default();
+
+        assert_eq!(config.column_count(80), 1);
+        assert_eq!(config.column_count(122), 2);
+        assert_eq!(config.column_count(200), 3);
+        assert_eq!(config.column_count(0), 1);
+        assert_eq!(
+            LayoutConfig {
+                columns: Some(2),
+                ..config
+            }
+            .column_count(200),
+            2
+        );
+    }
+
+    #[test]
+    fn huge_widths_and_gaps_dont_overflow() {
+        let wide = LayoutConfig {
+            column_width: u16::MAX,
+            ..LayoutConfig::default()
+        };
+        assert_eq!(wide.column_count(200), 1);
+
+        let gappy = LayoutConfig {
+            gap: u16::MAX,
+            ..LayoutConfig::default()
+        };
+        assert_eq!(gappy.column_count(200), 1);
+        assert_eq!(gappy.column_width(200, 3), 0);
+        let (areas, _) = arrange(&[item(1), item(2)], Rect::new(0, 0, 200, 10), 1, &gappy);
+        assert_eq!(areas[1], Rect::new(0, 1, 200, 2));
+    }
+
+    #[test]
+    fn flows_items_into_balanced_columns() {
+        let heading = LayoutItem {
+            height: 8,
+            full_width: true,
+        };
+        let items = [heading, item(1), item(1), item(3), item(1), item(2)];
+
+        let (areas, height) = arrange(&items, Rect::new(0, 0, 100, 0), 2, &LayoutConfig::default());
+
+        assert_eq!(height, 8 + 5);
+        assert_eq!(areas[0], Rect::new(0, 0, 100, 8));
+        assert_eq!(areas[1], Rect::new(0, 8, 49, 1));
+        assert_eq!(areas[2], Rect::new(0, 9, 49, 1));
+        assert_eq!(areas[3], Rect::new(0, 10, 49, 3));
+        assert_eq!(areas[4], Rect::new(51, 8, 49, 1));
+        assert_eq!(areas[5], Rect::new(51, 9, 49, 2));
+    }
+
+    #[test]
+    fn one_column_stacks_everything() {
+        let items = [item(1), item(2), item(3)];
+
+        let (areas, height) = arrange(&items, Rect::new(0, 0, 80, 0), 1, &LayoutConfig::default());
+
+        assert_eq!(height, 6);
+        assert_eq!(areas[2], Rect::new(0, 3, 80, 3));
+    }
+}
@@ -1,35 +1,296 @@
 use anyhow::Result;
 use ratatui::prelude::*;
 use ratatui::widgets::{Block, Padding, Paragraph};
+use serde::{de::DeserializeOwned, Serialize};
 use std::fmt::Debug;
+use std::future::Future;
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::time::Duration;
 
-pub trait SegmentRenderer<T: Info + ?Sized>: Debug + From<Box<T>> {
-    fn render(&self, frame: &mut Frame, area: Rect) -> Result<()>;
-    fn height(&self) -> u16;
+mod change;
+mod color;
+mod fit;
+mod layout;
+mod options;
+mod preflight;
+mod registry;
+mod system;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+pub mod text;
+mod theme;
+
+pub use change::*;
+pub use color::*;
+pub use fit::*;
+pub use layout::*;
+pub use options::*;
+pub use preflight::*;
+pub use registry::*;
+pub use system::*;
+pub use theme::*;
+
+pub trait SegmentRenderer<T: Info>: Debug + From<Box<T>> {
+    fn render(&self, frame: &mut Frame, area: Rect, theme: &Theme) -> Result<()>;
+    /// Rows the segment needs when rendered `width` columns wide with `theme`, so it can wrap
+    /// or abbreviate its content to fit.
+    fn height(&self, width: u16, theme: &Theme) -> u16;
+
+    /// Whether the segment spans every column when segments are laid out in columns, like the
+    /// heading, rather than taking a place in one of them.
+    fn full_width(&self) -> bool {
+        false
+    }
+
+    /// Short forms of the segment for `welcome2u summary`, like "load 0.42", styled with the
+    /// same thresholds as the full segment. Most segments have one; some have none or several.
+    fn summary(&self, _theme: &Theme) -> Vec<Line<'static>> {
+        Vec::new()
+    }
 }
 
-pub trait Info: Debug {}
+pub trait Info: Debug + Serialize + DeserializeOwned {
+    /// What changed since `previous`, the info shown `elapsed` ago, for the `changes` segment.
+    fn changes(&self, _previous: &Self, _elapsed: Duration) -> Vec<Change> {
+        Vec::new()
+    }
+}
 
 pub trait InfoBuilder<T: Info>: Debug {
     fn build(&self) -> impl std::future::Future<Output = Result<T>> + Send;
+
+    /// Cheap checks for what the builder depends on (files, sockets, platform support), so
+    /// `welcome2u doctor` can explain why a segment doesn't show up.
+    fn preflight(&self) -> Vec<Check> {
+        Vec::new()
+    }
+
+    /// How often `welcome2u watch` builds the segment again; `None` builds it only once, for
+    /// info that doesn't change while logged in.
+    fn refresh_interval(&self) -> Option<Duration> {
+        None
+    }
+}
+
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// Object-safe view of a [`SegmentRenderer`], so renderers of different types can live in one `Vec`.
+pub trait Segment: Debug + Send + Sync {
+    fn render(&self, frame: &mut Frame, area: Rect, theme: &Theme) -> Result<()>;
+    fn height(&self, width: u16, theme: &Theme) -> u16;
+
+    fn full_width(&self) -> bool {
+        false
+    }
+
+    fn summary(&self, _theme: &Theme) -> Vec<Line<'static>> {
+        Vec::new()
+    }
+}
+
+/// Object-safe view of an [`Info`], which can be serialized or turned into its renderer.
+pub trait SegmentInfo: Debug + Send {
+    fn to_json(&self) -> Result<serde_json::Value>;
+    fn into_segment(self: Box<Self>) -> Box<dyn Segment>;
+    /// What changed since `previous`, info serialized with [`SegmentInfo::to_json`] `elapsed`
+    /// ago. Info from an older version that no longer deserializes has no changes.
+    fn changes(&self, previous: serde_json::Value, elapsed: Duration) -> Vec<Change>;
+}
+
+/// Object-safe pairing of an [`InfoBuilder`] with the [`SegmentRenderer`] for its [`Info`].
+pub trait SegmentBuilder: Debug + Send + Sync {
+    fn build(&self) -> BoxFuture<'_, Result<Box<dyn SegmentInfo>>>;
+    /// Restore info previously serialized with [`SegmentInfo::to_json`], e.g. from a cache.
+    fn restore(&self, value: serde_json::Value) -> Result<Box<dyn SegmentInfo>>;
+    fn preflight(&self) -> Vec<Check>;
+    fn refresh_interval(&self) -> Option<Duration>;
+}
+
+/// Erase the types of `builder` and its renderer `R`, e.g. `boxed::<DiskInfo, DiskSegmentRenderer>(builder)`.
+pub fn boxed<T, R>(builder: impl InfoBuilder<T> + Send + Sync + 'static) -> Box<dyn SegmentBuilder>
+where
+    T: Info + Send + 'static,
+    R: SegmentRenderer<T> + Send + Sync + 'static,
+{
+    Box::new(BoxedBuilder::<_, T, R> {
+        builder,
+        marker: PhantomData,
+    })
+}
+
+/// Erase the types of `info` and its renderer `R`, for info that doesn't come from a builder,
+/// e.g. `boxed_info::<ChangesInfo, ChangesSegmentRenderer>(info)`.
+pub fn boxed_info<T, R>(info: T) -> Box<dyn SegmentInfo>
+where
+    T: Info + Send + 'static,
+    R: SegmentRenderer<T> + Send + Sync + 'static,
+{
+    Box::new(BoxedInfo::<T, R> {
+        info,
+        marker: PhantomData,
+    })
+}
+
+#[derive(Debug)]
+struct BoxedBuilder<B, T, R> {
+    builder: B,
+    marker: PhantomData<fn() -> (T, R)>,
+}
+
+impl<B, T, R> SegmentBuilder for BoxedBuilder<B, T, R>
+where
+    B: InfoBuilder<T> + Send + Sync,
+    T: Info + Send + 'static,
+    R: SegmentRenderer<T> + Send + Sync + 'static,
+{
+    fn build(&self) -> BoxFuture<'_, Result<Box<dyn SegmentInfo>>> {
+        Box::pin(async move {
+            let info = self.builder.build().await?;
+            Ok(boxed_info::<T, R>(info))
+        })
+    }
+
+    fn restore(&self, value: serde_json::Value) -> Result<Box<dyn SegmentInfo>> {
+        Ok(boxed_info::<T, R>(serde_json::from_value(value)?))
+    }
+
+    fn preflight(&self) -> Vec<Check> {
+        self.builder.preflight()
+    }
+
+    fn refresh_interval(&self) -> Option<Duration> {
+        self.builder.refresh_interval()
+    }
 }
 
+#[derive(Debug)]
+struct BoxedInfo<T, R> {
+    info: T,
+    marker: PhantomData<fn() -> R>,
+}
+
+impl<T, R> SegmentInfo for BoxedInfo<T, R>
+where
+    T: Info + Send + 'static,
+    R: SegmentRenderer<T> + Send + Sync + 'static,
+{
+    fn to_json(&self) -> Result<serde_json::Value> {
+        Ok(serde_json::to_value(&self.info)?)
+    }
+
+    fn into_segment(self: Box<Self>) -> Box<dyn Segment> {
+        Box::new(BoxedRenderer::<T, R> {
+            renderer: R::from(Box::new(self.info)),
+            marker: PhantomData,
+        })
+    }
+
+    fn changes(&self, previous: serde_json::Value, elapsed: Duration) -> Vec<Change> {
+        match serde_json::from_value(previous) {
+            Ok(previous) => self.info.changes(&previous, elapsed),
+            Err(_) => Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct BoxedRenderer<T, R> {
+    renderer: R,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T, R> Segment for BoxedRenderer<T, R>
+where
+    T: Info,
+    R: SegmentRenderer<T> + Send + Sync,
+{
+    fn render(&self, frame: &mut Frame, area: Rect, theme: &Theme) -> Result<()> {
+        self.renderer.render(frame, area, theme)
+    }
+
+    fn height(&self, width: u16, theme: &Theme) -> u16 {
+        self.renderer.height(width, theme)
+    }
+
+    fn full_width(&self) -> bool {
+        self.renderer.full_width()
+    }
+
+    fn summary(&self, theme: &Theme) -> Vec<Line<'static>> {
+        self.renderer.summary(theme)
+    }
+}
+
+/// Stands in for a segment whose info couldn't be built, as a dim "unavailable: <reason>" row.
+#[derive(Debug)]
+pub struct UnavailableSegment {
+    name: String,
+    reason: String,
+}
+
+impl UnavailableSegment {
+    pub fn new(name: &str, reason: String) -> Self {
+        let mut chars = name.chars();
+        let name = match chars.next() {
+            Some(first) => first.to_uppercase().chain(chars).collect(),
+            None => String::new(),
+        };
+        Self { name, reason }
+    }
+}
+
+impl Segment for UnavailableSegment {
+    fn render(&self, frame: &mut Frame, area: Rect, theme: &Theme) -> Result<()> {
+        let [label_area, data_area, _padding] = create_label_data_layout(area);
+
+        frame.render_widget(label(&self.name, theme), label_area);
+        let reason = format!("unavailable: {}", self.reason);
+        frame.render_widget(
+            Paragraph::new(truncate(&reason, data_area.width as usize)).style(theme.dim),
+            data_area,
+        );
+
+        Ok(())
+    }
+
+    fn height(&self, _width: u16, _theme: &Theme) -> u16 {
+        1
+    }
+}
+
+/// Columns for the label on the left of [`create_label_data_layout`], and the padding on the right.
+const LABEL_WIDTH: u16 = 16;
+const PADDING_WIDTH: u16 = 4;
+
 pub fn create_label_data_layout(area: Rect) -> [Rect; 3] {
     let layout = Layout::default()
         .direction(Direction::Horizontal)
         .constraints(vec![
-            Constraint::Length(16),
+            Constraint::Length(LABEL_WIDTH),
             Constraint::Fill(1),
-            Constraint::Length(4),
+            Constraint::Length(PADDING_WIDTH),
         ]);
 
     layout.areas(area)
 }
 
-pub fn label(text: &str) -> Paragraph<'_> {
+/// Width of the data area [`create_label_data_layout`] leaves in an area `width` columns wide.
+pub fn data_width(width: u16) -> u16 {
+    width.saturating_sub(LABEL_WIDTH + PADDING_WIDTH)
+}
+
+pub fn label<'a>(text: &'a str, theme: &Theme) -> Paragraph<'a> {
     Paragraph::new(text)
-        .fg(Color::Blue)
-        .bold()
+        .style(theme.label)
         .alignment(Alignment::Right)
         .block(Block::default().padding(Padding::new(0, 2, 0, 0)))
 }
+
+/// One item of a segment's summary: `name` followed by `value` in `style`, like "mem 63%".
+pub fn summary_item(name: &str, value: String, style: Style) -> Line<'static> {
+    Line::from(vec![
+        Span::raw(format!("{name} ")),
+        Span::styled(value, style),
+    ])
+}
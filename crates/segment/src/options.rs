@@ -0,0 +1,53 @@
+use serde::de::DeserializeOwned;
+use std::path::{Path, PathBuf};
+
+use crate::SharedSystem;
+
+/// The `[segment.<name>]` table from the configuration, minus the keys handled by the binary,
+/// along with what the segment should read host data from.
+#[derive(Debug, Clone, Default)]
+pub struct SegmentOptions {
+    table: toml::Table,
+    system: SharedSystem,
+    seed: Option<u64>,
+    state_dir: Option<PathBuf>,
+}
+
+impl SegmentOptions {
+    pub fn new(table: toml::Table, system: SharedSystem) -> Self {
+        Self {
+            table,
+            system,
+            ..Self::default()
+        }
+    }
+
+    pub fn with_seed(mut self, seed: Option<u64>) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    pub fn with_state_dir(mut self, state_dir: Option<PathBuf>) -> Self {
+        self.state_dir = state_dir;
+        self
+    }
+
+    /// Deserialize the options into `T`, usually the segment's `InfoBuilder`.
+    pub fn parse<T: DeserializeOwned>(&self) -> Result<T, toml::de::Error> {
+        toml::Value::Table(self.table.clone()).try_into()
+    }
+
+    pub fn system(&self) -> SharedSystem {
+        self.system.clone()
+    }
+
+    /// What to seed random choices with instead of entropy, so output can be reproduced.
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    /// Where the segment may keep files that remember earlier runs, if anywhere.
+    pub fn state_dir(&self) -> Option<&Path> {
+        self.state_dir.as_deref()
+    }
+}
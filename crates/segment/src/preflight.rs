@@ -0,0 +1,85 @@
+use serde::Serialize;
+use std::fmt;
+use std::fs::{self, File};
+use std::io;
+use std::path::Path;
+
+/// Result of one of a segment's preflight checks, e.g. that a file it reads exists.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Check {
+    /// What was checked, like a path or a command.
+    pub subject: String,
+    #[serde(flatten)]
+    pub status: CheckStatus,
+    /// How to fix the problem, shown when the check doesn't pass.
+    pub hint: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case", tag = "status", content = "message")]
+pub enum CheckStatus {
+    Ok,
+    Missing,
+    PermissionDenied,
+    Unsupported,
+    Failed(String),
+}
+
+impl Check {
+    pub fn new(subject: impl Into<String>, status: CheckStatus) -> Self {
+        Self {
+            subject: subject.into(),
+            status,
+            hint: None,
+        }
+    }
+
+    pub fn ok(subject: impl Into<String>) -> Self {
+        Self::new(subject, CheckStatus::Ok)
+    }
+
+    /// Check that the file at `path` can be opened for reading.
+    pub fn readable(path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref();
+        Self::from_io(path, File::open(path).map(drop))
+    }
+
+    /// Check that something exists at `path`, without opening it, e.g. for sockets.
+    pub fn exists(path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref();
+        Self::from_io(path, fs::metadata(path).map(drop))
+    }
+
+    fn from_io(path: &Path, result: io::Result<()>) -> Self {
+        let status = match result {
+            Ok(()) => CheckStatus::Ok,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => CheckStatus::Missing,
+            Err(error) if error.kind() == io::ErrorKind::PermissionDenied => {
+                CheckStatus::PermissionDenied
+            }
+            Err(error) => CheckStatus::Failed(error.to_string()),
+        };
+        Self::new(path.display().to_string(), status)
+    }
+
+    pub fn with_hint(mut self, hint: impl Into<String>) -> Self {
+        self.hint = Some(hint.into());
+        self
+    }
+
+    pub fn passed(&self) -> bool {
+        self.status == CheckStatus::Ok
+    }
+}
+
+impl fmt::Display for CheckStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CheckStatus::Ok => f.write_str("OK"),
+            CheckStatus::Missing => f.write_str("missing"),
+            CheckStatus::PermissionDenied => f.write_str("permission denied"),
+            CheckStatus::Unsupported => f.write_str("unsupported platform"),
+            CheckStatus::Failed(message) => write!(f, "failed: {message}"),
+        }
+    }
+}
@@ -0,0 +1,109 @@
+use std::fmt;
+use std::path::PathBuf;
+use thiserror::Error;
+
+use crate::{SegmentBuilder, SegmentOptions, SharedSystem};
+
+/// Creates a builder for a registered segment from its configured options.
+pub type SegmentFactory =
+    Box<dyn Fn(&SegmentOptions) -> Result<Box<dyn SegmentBuilder>, toml::de::Error> + Send + Sync>;
+
+#[derive(Error, Debug)]
+pub enum RegistryError {
+    #[error("Unknown segment `{name}`, expected one of: {known}")]
+    UnknownSegment { name: String, known: String },
+    #[error("Invalid options for segment `{name}`")]
+    InvalidOptions {
+        name: String,
+        source: toml::de::Error,
+    },
+}
+
+/// Named segment factories, in registration order.
+///
+/// Each segment crate exposes a `register` function that adds itself here, so the binary
+/// only has to decide which names to build, in what order, and with which options.
+#[derive(Default)]
+pub struct Registry {
+    factories: Vec<(&'static str, SegmentFactory)>,
+    system: SharedSystem,
+    seed: Option<u64>,
+    state_dir: Option<PathBuf>,
+}
+
+impl Registry {
+    /// Register `factory` under `name`, replacing any segment previously registered with that name.
+    pub fn register<F>(&mut self, name: &'static str, factory: F) -> &mut Self
+    where
+        F: Fn(&SegmentOptions) -> Result<Box<dyn SegmentBuilder>, toml::de::Error>
+            + Send
+            + Sync
+            + 'static,
+    {
+        let factory: SegmentFactory = Box::new(factory);
+        match self.factories.iter_mut().find(|(n, _)| *n == name) {
+            Some(entry) => entry.1 = factory,
+            None => self.factories.push((name, factory)),
+        }
+        self
+    }
+
+    /// Have segments created from now on read host data from `system`.
+    pub fn set_system(&mut self, system: SharedSystem) -> &mut Self {
+        self.system = system;
+        self
+    }
+
+    /// Have segments created from now on make their random choices from `seed`.
+    pub fn set_seed(&mut self, seed: Option<u64>) -> &mut Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Have segments created from now on keep what they remember between runs in `state_dir`.
+    pub fn set_state_dir(&mut self, state_dir: Option<PathBuf>) -> &mut Self {
+        self.state_dir = state_dir;
+        self
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.factories.iter().map(|(name, _)| *name)
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.factories.iter().any(|(n, _)| *n == name)
+    }
+
+    pub fn create(
+        &self,
+        name: &str,
+        options: &toml::Table,
+    ) -> Result<Box<dyn SegmentBuilder>, RegistryError> {
+        let (_, factory) = self
+            .factories
+            .iter()
+            .find(|(n, _)| *n == name)
+            .ok_or_else(|| self.unknown_segment(name))?;
+
+        let options = SegmentOptions::new(options.clone(), self.system.clone())
+            .with_seed(self.seed)
+            .with_state_dir(self.state_dir.clone());
+        factory(&options).map_err(|source| RegistryError::InvalidOptions {
+            name: name.to_string(),
+            source,
+        })
+    }
+
+    pub fn unknown_segment(&self, name: &str) -> RegistryError {
+        RegistryError::UnknownSegment {
+            name: name.to_string(),
+            known: self.names().collect::<Vec<_>>().join(", "),
+        }
+    }
+}
+
+impl fmt::Debug for Registry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.names()).finish()
+    }
+}
@@ -0,0 +1,327 @@
+//! Where segments get data about the host from, so it can be swapped for a captured snapshot.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt::Debug;
+use std::fs;
+use std::ops::Deref;
+use std::path::Path;
+use std::sync::{Arc, OnceLock};
+use tracing::instrument;
+
+/// Load averages over the last 1, 5 and 15 minutes.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LoadAverage {
+    pub one: f64,
+    pub five: f64,
+    pub fifteen: f64,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Memory {
+    pub total_bytes: u64,
+    pub used_bytes: u64,
+    pub available_bytes: u64,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiskUsage {
+    pub name: String,
+    pub mount_point: String,
+    pub total_bytes: u64,
+    pub available_bytes: u64,
+}
+
+/// A temperature sensor, in degrees Celsius.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Component {
+    pub label: String,
+    pub temperature: f32,
+    pub max: f32,
+    pub critical: Option<f32>,
+}
+
+/// Data about the host that segments read, rather than asking `sysinfo` themselves.
+pub trait SystemProvider: Debug + Send + Sync {
+    fn load_average(&self) -> LoadAverage;
+    fn physical_core_count(&self) -> Option<usize>;
+    fn memory(&self) -> Memory;
+    fn disks(&self) -> Vec<DiskUsage>;
+    fn components(&self) -> Vec<Component>;
+    /// Seconds since the host booted.
+    fn uptime(&self) -> u64;
+}
+
+/// A [`SystemProvider`] that can be handed to any number of builders. Unless told otherwise it
+/// reads the real host, refreshing each subsystem once no matter how many segments use it.
+#[derive(Debug, Clone)]
+pub struct SharedSystem(Arc<dyn SystemProvider>);
+
+impl SharedSystem {
+    pub fn new(provider: impl SystemProvider + 'static) -> Self {
+        Self(Arc::new(provider))
+    }
+}
+
+impl Default for SharedSystem {
+    fn default() -> Self {
+        Self::new(CachedSystem::new(SysinfoProvider))
+    }
+}
+
+impl Deref for SharedSystem {
+    type Target = dyn SystemProvider;
+
+    fn deref(&self) -> &Self::Target {
+        self.0.as_ref()
+    }
+}
+
+/// Reads the real host with `sysinfo`, refreshing only what each call needs.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SysinfoProvider;
+
+impl SystemProvider for SysinfoProvider {
+    #[instrument(skip(self))]
+    fn load_average(&self) -> LoadAverage {
+        let load = sysinfo::System::load_average();
+        LoadAverage {
+            one: load.one,
+            five: load.five,
+            fifteen: load.fifteen,
+        }
+    }
+
+    #[instrument(skip(self))]
+    fn physical_core_count(&self) -> Option<usize> {
+        sysinfo::System::new().physical_core_count()
+    }
+
+    #[instrument(skip(self))]
+    fn memory(&self) -> Memory {
+        let mut sys = sysinfo::System::new();
+        sys.refresh_memory();
+        Memory {
+            total_bytes: sys.total_memory(),
+            used_bytes: sys.used_memory(),
+            available_bytes: sys.available_memory(),
+        }
+    }
+
+    #[instrument(skip(self))]
+    fn disks(&self) -> Vec<DiskUsage> {
+        sysinfo::Disks::new_with_refreshed_list()
+            .iter()
+            .map(|disk| DiskUsage {
+                name: disk.name().to_string_lossy().into_owned(),
+                mount_point: disk.mount_point().to_string_lossy().into_owned(),
+                total_bytes: disk.total_space(),
+                available_bytes: disk.available_space(),
+            })
+            .collect()
+    }
+
+    #[instrument(skip(self))]
+    fn components(&self) -> Vec<Component> {
+        sysinfo::Components::new_with_refreshed_list()
+            .iter()
+            .map(|component| Component {
+                label: component.label().to_string(),
+                temperature: component.temperature(),
+                max: component.max(),
+                critical: component.critical(),
+            })
+            .collect()
+    }
+
+    #[instrument(skip(self))]
+    fn uptime(&self) -> u64 {
+        sysinfo::System::uptime()
+    }
+}
+
+/// Asks the wrapped provider for each kind of data at most once, so segments sharing it don't
+/// refresh the same `sysinfo` subsystem over and over, and subsystems no segment asks for are
+/// never refreshed at all.
+#[derive(Debug, Default)]
+pub struct CachedSystem<P> {
+    provider: P,
+    load_average: OnceLock<LoadAverage>,
+    physical_core_count: OnceLock<Option<usize>>,
+    memory: OnceLock<Memory>,
+    disks: OnceLock<Vec<DiskUsage>>,
+    components: OnceLock<Vec<Component>>,
+    uptime: OnceLock<u64>,
+}
+
+impl<P: SystemProvider> CachedSystem<P> {
+    pub fn new(provider: P) -> Self {
+        Self {
+            provider,
+            load_average: OnceLock::new(),
+            physical_core_count: OnceLock::new(),
+            memory: OnceLock::new(),
+            disks: OnceLock::new(),
+            components: OnceLock::new(),
+            uptime: OnceLock::new(),
+        }
+    }
+}
+
+impl<P: SystemProvider> SystemProvider for CachedSystem<P> {
+    fn load_average(&self) -> LoadAverage {
+        *self
+            .load_average
+            .get_or_init(|| self.provider.load_average())
+    }
+
+    fn physical_core_count(&self) -> Option<usize> {
+        *self
+            .physical_core_count
+            .get_or_init(|| self.provider.physical_core_count())
+    }
+
+    fn memory(&self) -> Memory {
+        *self.memory.get_or_init(|| self.provider.memory())
+    }
+
+    fn disks(&self) -> Vec<DiskUsage> {
+        self.disks.get_or_init(|| self.provider.disks()).clone()
+    }
+
+    fn components(&self) -> Vec<Component> {
+        self.components
+            .get_or_init(|| self.provider.components())
+            .clone()
+    }
+
+    fn uptime(&self) -> u64 {
+        *self.uptime.get_or_init(|| self.provider.uptime())
+    }
+}
+
+/// Everything a [`SystemProvider`] reports, captured at one point in time.
+///
+/// Captured with `welcome2u capture-system` and read back with `--system <PATH>`, to see how
+/// another host's MOTD renders or to use as a test fixture.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SystemSnapshot {
+    pub load_average: LoadAverage,
+    pub physical_core_count: Option<usize>,
+    pub memory: Memory,
+    pub disks: Vec<DiskUsage>,
+    pub components: Vec<Component>,
+    pub uptime_seconds: u64,
+}
+
+impl SystemSnapshot {
+    pub fn capture(provider: &dyn SystemProvider) -> Self {
+        Self {
+            load_average: provider.load_average(),
+            physical_core_count: provider.physical_core_count(),
+            memory: provider.memory(),
+            disks: provider.disks(),
+            components: provider.components(),
+            uptime_seconds: provider.uptime(),
+        }
+    }
+
+    /// Read a snapshot from a `.toml` file, or JSON otherwise.
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let snapshot = if path
+            .extension()
+            .is_some_and(|extension| extension == "toml")
+        {
+            toml::from_str(&contents).map_err(anyhow::Error::from)
+        } else {
+            serde_json::from_str(&contents).map_err(anyhow::Error::from)
+        };
+        snapshot.with_context(|| format!("Failed to parse {}", path.display()))
+    }
+}
+
+impl SystemProvider for SystemSnapshot {
+    fn load_average(&self) -> LoadAverage {
+        self.load_average
+    }
+
+    fn physical_core_count(&self) -> Option<usize> {
+        self.physical_core_count
+    }
+
+    fn memory(&self) -> Memory {
+        self.memory
+    }
+
+    fn disks(&self) -> Vec<DiskUsage> {
+        self.disks.clone()
+    }
+
+    fn components(&self) -> Vec<Component> {
+        self.components.clone()
+    }
+
+    fn uptime(&self) -> u64 {
+        self.uptime_seconds
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[derive(Debug, Default)]
+    struct CountingProvider {
+        calls: AtomicUsize,
+    }
+
+    impl SystemProvider for CountingProvider {
+        fn load_average(&self) -> LoadAverage {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            LoadAverage::default()
+        }
+
+        fn physical_core_count(&self) -> Option<usize> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Some(8)
+        }
+
+        fn memory(&self) -> Memory {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Memory::default()
+        }
+
+        fn disks(&self) -> Vec<DiskUsage> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Vec::new()
+        }
+
+        fn components(&self) -> Vec<Component> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Vec::new()
+        }
+
+        fn uptime(&self) -> u64 {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            42
+        }
+    }
+
+    #[test]
+    fn cached_system_refreshes_each_subsystem_once() {
+        let system = CachedSystem::new(CountingProvider::default());
+
+        assert_eq!(system.uptime(), 42);
+        assert_eq!(system.uptime(), 42);
+        assert_eq!(system.physical_core_count(), Some(8));
+        system.memory();
+        system.memory();
+
+        // Disks, components and load were never asked for, so never refreshed
+        assert_eq!(system.provider.calls.load(Ordering::SeqCst), 3);
+    }
+}
@@ -0,0 +1,176 @@
+//! Helpers for rendering segments headlessly and comparing them against stored snapshots.
+//!
+//! Enabled with the `testing` feature, so segment crates can pull it in as a dev-dependency:
+//!
+//! ```toml
+//! [dev-dependencies]
+//! segment = { path = "../../crates/segment", features = ["testing"] }
+//! ```
+//!
+//! Fixtures are an [`Info`] serialized as JSON, like `welcome2u --format json` prints, stored
+//! in the crate's `tests/fixtures`. Snapshots are stored in `tests/snapshots`; run the tests
+//! with `UPDATE_SNAPSHOTS=1` to write them after an intended change.
+
+use ratatui::backend::TestBackend;
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Modifier};
+use ratatui::text::Line;
+use ratatui::Terminal;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+use crate::text::{rows, to_plain};
+use crate::{Info, SegmentRenderer, Theme};
+
+/// Render `info` with its renderer `R` into a buffer `width` columns wide and as tall as the
+/// renderer asks for, using the default theme.
+pub fn render<T: Info, R: SegmentRenderer<T>>(info: T, width: u16) -> Buffer {
+    render_with_theme::<T, R>(info, width, &Theme::default())
+}
+
+pub fn render_with_theme<T: Info, R: SegmentRenderer<T>>(
+    info: T,
+    width: u16,
+    theme: &Theme,
+) -> Buffer {
+    let renderer = R::from(Box::new(info));
+    let height = renderer.height(width, theme);
+    if height == 0 {
+        return Buffer::empty(Rect::new(0, 0, width, 0));
+    }
+
+    let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+    terminal
+        .draw(|frame| renderer.render(frame, frame.area(), theme).unwrap())
+        .unwrap();
+    terminal.backend().buffer().clone()
+}
+
+/// The summary items `info`'s renderer `R` gives with the default theme.
+pub fn summary<T: Info, R: SegmentRenderer<T>>(info: T) -> Vec<Line<'static>> {
+    R::from(Box::new(info)).summary(&Theme::default())
+}
+
+/// Load an [`Info`] from the JSON fixture at `path`.
+pub fn load_fixture<T: Info>(path: impl AsRef<Path>) -> T {
+    let path = path.as_ref();
+    let contents = fs::read_to_string(path)
+        .unwrap_or_else(|error| panic!("Failed to read fixture {}: {error}", path.display()));
+    serde_json::from_str(&contents)
+        .unwrap_or_else(|error| panic!("Invalid fixture {}: {error}", path.display()))
+}
+
+/// Describe `buffer` for a snapshot: its plain text, followed by each run of styled cells
+/// as `<row> <columns>: <style>`.
+pub fn describe(buffer: &Buffer) -> String {
+    let mut output = to_plain(buffer);
+    output.push_str("---\n");
+
+    for (y, row) in rows(buffer).enumerate() {
+        let mut start = 0;
+        while start < row.len() {
+            let style = (row[start].fg, row[start].bg, row[start].modifier);
+            let end = start
+                + row[start..]
+                    .iter()
+                    .take_while(|cell| (cell.fg, cell.bg, cell.modifier) == style)
+                    .count();
+            if style != (Color::Reset, Color::Reset, Modifier::empty()) {
+                let _ = writeln!(output, "{y} {start}..{end}: {}", describe_style(style));
+            }
+            start = end;
+        }
+    }
+
+    output
+}
+
+fn describe_style((fg, bg, modifier): (Color, Color, Modifier)) -> String {
+    let mut parts = Vec::new();
+    if fg != Color::Reset {
+        parts.push(format!("fg={fg}"));
+    }
+    if bg != Color::Reset {
+        parts.push(format!("bg={bg}"));
+    }
+    for (name, _) in modifier.iter_names() {
+        parts.push(name.to_lowercase());
+    }
+    parts.join(" ")
+}
+
+/// Compare `actual` with the snapshot at `path`, or write it there when `UPDATE_SNAPSHOTS`
+/// is set.
+pub fn check_snapshot(path: impl AsRef<Path>, actual: &str) {
+    let path = path.as_ref();
+
+    if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).unwrap();
+        }
+        fs::write(path, actual).unwrap();
+        return;
+    }
+
+    let expected = fs::read_to_string(path).unwrap_or_else(|error| {
+        panic!(
+            "Failed to read snapshot {}: {error}; run with UPDATE_SNAPSHOTS=1 to create it\n\
+             --- actual\n{actual}",
+            path.display()
+        )
+    });
+    assert!(
+        expected == actual,
+        "Snapshot {} doesn't match; run with UPDATE_SNAPSHOTS=1 if the change is intended\n\
+         --- expected\n{expected}--- actual\n{actual}",
+        path.display()
+    );
+}
+
+/// Load `tests/fixtures/<name>.json` from the calling crate.
+#[macro_export]
+macro_rules! fixture {
+    ($name:expr) => {
+        $crate::testing::load_fixture(format!(
+            "{}/tests/fixtures/{}.json",
+            env!("CARGO_MANIFEST_DIR"),
+            $name
+        ))
+    };
+}
+
+/// Compare a rendered buffer with `tests/snapshots/<name>.snap` in the calling crate.
+#[macro_export]
+macro_rules! assert_snapshot {
+    ($name:expr, $buffer:expr) => {
+        $crate::testing::check_snapshot(
+            format!(
+                "{}/tests/snapshots/{}.snap",
+                env!("CARGO_MANIFEST_DIR"),
+                $name
+            ),
+            &$crate::testing::describe(&$buffer),
+        )
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::style::{Style, Stylize};
+
+    #[test]
+    fn describe_lists_styled_runs() {
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 10, 2));
+        buffer.set_string(0, 0, "Disk", Style::new().fg(Color::Blue).bold());
+        buffer.set_string(5, 0, "ok", Style::new());
+        buffer.set_string(0, 1, "90%", Style::new().fg(Color::Red).bg(Color::Black));
+
+        assert_eq!(
+            describe(&buffer),
+            "Disk ok\n90%\n---\n0 0..4: fg=Blue bold\n1 0..3: fg=Red bg=Black\n"
+        );
+    }
+}
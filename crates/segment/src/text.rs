@@ -0,0 +1,126 @@
+//! Serialize a rendered [`Buffer`] as text, for output that isn't an interactive terminal.
+
+use ratatui::buffer::{Buffer, Cell};
+use ratatui::style::{Color, Modifier};
+use std::fmt::Write;
+use unicode_width::UnicodeWidthStr;
+
+/// The buffer's text without any styling, one line per row with trailing spaces removed.
+pub fn to_plain(buffer: &Buffer) -> String {
+    let mut output = String::new();
+    for row in rows(buffer) {
+        let mut line = String::new();
+        for cell in row {
+            line.push_str(cell.symbol());
+        }
+        output.push_str(line.trim_end());
+        output.push('\n');
+    }
+    output
+}
+
+/// The buffer's text with its styles as ANSI SGR escape sequences.
+pub fn to_ansi(buffer: &Buffer) -> String {
+    let mut output = String::new();
+    for row in rows(buffer) {
+        // Leave off trailing blank cells, unless they have a background to show
+        let end = row
+            .iter()
+            .rposition(|cell| cell.symbol() != " " || cell.bg != Color::Reset)
+            .map_or(0, |index| index + 1);
+
+        let mut current = PLAIN;
+        for cell in &row[..end] {
+            let style = (cell.fg, cell.bg, cell.modifier);
+            if style != current {
+                output.push_str(&sgr(style));
+                current = style;
+            }
+            output.push_str(cell.symbol());
+        }
+        if current != PLAIN {
+            output.push_str("\x1b[0m");
+        }
+        output.push('\n');
+    }
+    output
+}
+
+type CellStyle = (Color, Color, Modifier);
+
+const PLAIN: CellStyle = (Color::Reset, Color::Reset, Modifier::empty());
+
+/// Each row's cells, leaving out the cells hidden behind wide characters.
+pub(crate) fn rows(buffer: &Buffer) -> impl Iterator<Item = Vec<&Cell>> {
+    let width = buffer.area.width as usize;
+    buffer.content.chunks(width.max(1)).map(|row| {
+        let mut cells = Vec::with_capacity(row.len());
+        let mut to_skip = 0;
+        for cell in row {
+            if to_skip > 0 {
+                to_skip -= 1;
+                continue;
+            }
+            if cell.skip {
+                continue;
+            }
+            to_skip = cell.symbol().width().saturating_sub(1);
+            cells.push(cell);
+        }
+        cells
+    })
+}
+
+/// A reset followed by everything needed to switch to `style`.
+fn sgr((fg, bg, modifiers): CellStyle) -> String {
+    let mut codes = vec!["0".to_string()];
+
+    for (modifier, code) in [
+        (Modifier::BOLD, "1"),
+        (Modifier::DIM, "2"),
+        (Modifier::ITALIC, "3"),
+        (Modifier::UNDERLINED, "4"),
+        (Modifier::SLOW_BLINK, "5"),
+        (Modifier::RAPID_BLINK, "6"),
+        (Modifier::REVERSED, "7"),
+        (Modifier::HIDDEN, "8"),
+        (Modifier::CROSSED_OUT, "9"),
+    ] {
+        if modifiers.contains(modifier) {
+            codes.push(code.to_string());
+        }
+    }
+
+    codes.extend(color_code(fg, false));
+    codes.extend(color_code(bg, true));
+
+    let mut sequence = String::from("\x1b[");
+    let _ = write!(sequence, "{}m", codes.join(";"));
+    sequence
+}
+
+fn color_code(color: Color, background: bool) -> Option<String> {
+    let offset = if background { 10 } else { 0 };
+    let basic = |code: u8| Some((code + offset).to_string());
+    match color {
+        Color::Reset => None,
+        Color::Black => basic(30),
+        Color::Red => basic(31),
+        Color::Green => basic(32),
+        Color::Yellow => basic(33),
+        Color::Blue => basic(34),
+        Color::Magenta => basic(35),
+        Color::Cyan => basic(36),
+        Color::Gray => basic(37),
+        Color::DarkGray => basic(90),
+        Color::LightRed => basic(91),
+        Color::LightGreen => basic(92),
+        Color::LightYellow => basic(93),
+        Color::LightBlue => basic(94),
+        Color::LightMagenta => basic(95),
+        Color::LightCyan => basic(96),
+        Color::White => basic(97),
+        Color::Indexed(index) => Some(format!("{};5;{index}", 38 + offset)),
+        Color::Rgb(r, g, b) => Some(format!("{};2;{r};{g};{b}", 38 + offset)),
+    }
+}
@@ -0,0 +1,144 @@
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::symbols;
+use serde::Deserialize;
+
+use crate::Severity;
+
+/// Styles and glyphs segments draw with, so the whole MOTD can be restyled in one place.
+#[derive(Debug, Clone)]
+pub struct Theme {
+    /// Segment labels, like "Disk" and "RAM".
+    pub label: Style,
+    /// Values within their thresholds.
+    pub ok: Style,
+    /// Values past their warning threshold.
+    pub warn: Style,
+    /// Values past their critical threshold, and errors.
+    pub critical: Style,
+    /// Secondary text, like the quote and unavailable segments.
+    pub dim: Style,
+    /// The heading's figlet art; `None` colors it like a rainbow.
+    pub heading: Option<Style>,
+    pub gauge_filled: Style,
+    pub gauge_unfilled: Style,
+    pub gauge_line: symbols::line::Set,
+    /// Between items on one line, like load averages and temperatures.
+    pub separator: &'static str,
+}
+
+/// The built-in themes, as named in the configuration's `theme` key.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ThemeName {
+    #[default]
+    Default,
+    Solarized,
+    Monochrome,
+    HighContrast,
+}
+
+impl ThemeName {
+    pub fn theme(self) -> Theme {
+        match self {
+            ThemeName::Default => Theme::default(),
+            ThemeName::Solarized => Theme::solarized(),
+            ThemeName::Monochrome => Theme::monochrome(),
+            ThemeName::HighContrast => Theme::high_contrast(),
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            label: Style::new().fg(Color::Blue).add_modifier(Modifier::BOLD),
+            ok: Style::new().fg(Color::Green),
+            warn: Style::new().fg(Color::Yellow),
+            critical: Style::new().fg(Color::Red),
+            dim: Style::new().add_modifier(Modifier::DIM),
+            heading: None,
+            gauge_filled: Style::new().fg(Color::Red).add_modifier(Modifier::BOLD),
+            gauge_unfilled: Style::new().fg(Color::Green),
+            gauge_line: symbols::line::THICK,
+            separator: ", ",
+        }
+    }
+}
+
+impl Theme {
+    /// Ethan Schoonover's Solarized accents, which read well on light and dark backgrounds.
+    pub fn solarized() -> Self {
+        const BASE01: Color = Color::Rgb(0x58, 0x6e, 0x75);
+        const YELLOW: Color = Color::Rgb(0xb5, 0x89, 0x00);
+        const ORANGE: Color = Color::Rgb(0xcb, 0x4b, 0x16);
+        const RED: Color = Color::Rgb(0xdc, 0x32, 0x2f);
+        const BLUE: Color = Color::Rgb(0x26, 0x8b, 0xd2);
+        const CYAN: Color = Color::Rgb(0x2a, 0xa1, 0x98);
+        const GREEN: Color = Color::Rgb(0x85, 0x99, 0x00);
+
+        Self {
+            label: Style::new().fg(BLUE).add_modifier(Modifier::BOLD),
+            ok: Style::new().fg(GREEN),
+            warn: Style::new().fg(YELLOW),
+            critical: Style::new().fg(RED),
+            dim: Style::new().fg(BASE01),
+            heading: Some(Style::new().fg(CYAN)),
+            gauge_filled: Style::new().fg(ORANGE),
+            gauge_unfilled: Style::new().fg(BASE01),
+            gauge_line: symbols::line::THICK,
+            separator: ", ",
+        }
+    }
+
+    /// No colors at all, only weight and emphasis.
+    pub fn monochrome() -> Self {
+        Self {
+            label: Style::new().add_modifier(Modifier::BOLD),
+            ok: Style::new(),
+            warn: Style::new().add_modifier(Modifier::BOLD),
+            critical: Style::new().add_modifier(Modifier::BOLD | Modifier::REVERSED),
+            dim: Style::new().add_modifier(Modifier::DIM),
+            heading: Some(Style::new()),
+            gauge_filled: Style::new().add_modifier(Modifier::BOLD),
+            gauge_unfilled: Style::new().add_modifier(Modifier::DIM),
+            gauge_line: symbols::line::THICK,
+            separator: " · ",
+        }
+    }
+
+    /// Bright colors and no dim text, for low-contrast terminals and low vision.
+    pub fn high_contrast() -> Self {
+        Self {
+            label: Style::new()
+                .fg(Color::LightCyan)
+                .add_modifier(Modifier::BOLD),
+            ok: Style::new()
+                .fg(Color::LightGreen)
+                .add_modifier(Modifier::BOLD),
+            warn: Style::new()
+                .fg(Color::LightYellow)
+                .add_modifier(Modifier::BOLD),
+            critical: Style::new()
+                .fg(Color::White)
+                .bg(Color::Red)
+                .add_modifier(Modifier::BOLD),
+            dim: Style::new().fg(Color::Gray),
+            heading: Some(Style::new().fg(Color::White).add_modifier(Modifier::BOLD)),
+            gauge_filled: Style::new()
+                .fg(Color::LightRed)
+                .add_modifier(Modifier::BOLD),
+            gauge_unfilled: Style::new().fg(Color::White),
+            gauge_line: symbols::line::DOUBLE,
+            separator: ", ",
+        }
+    }
+
+    /// The `ok`, `warn` or `critical` style for a value compared against its thresholds.
+    pub fn status(&self, value: f64, warning: f64, critical: f64) -> Style {
+        match Severity::from_thresholds(value, warning, critical) {
+            Severity::Notice => self.ok,
+            Severity::Warning => self.warn,
+            Severity::Critical => self.critical,
+        }
+    }
+}
@@ -0,0 +1,116 @@
+use anyhow::Result;
+use ratatui::{prelude::*, widgets::*};
+use segment::*;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tracing::instrument;
+
+#[derive(Default, Debug)]
+pub struct ChangesSegmentRenderer {
+    info: ChangesInfo,
+}
+
+/// What changed in the other segments since the MOTD was last shown.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ChangesInfo {
+    changes: Vec<Change>,
+    /// How long ago the MOTD was last shown, if ever.
+    elapsed_seconds: Option<u64>,
+}
+
+impl ChangesInfo {
+    pub fn new(changes: Vec<Change>, elapsed: Option<Duration>) -> Self {
+        Self {
+            changes,
+            elapsed_seconds: elapsed.map(|elapsed| elapsed.as_secs()),
+        }
+    }
+}
+
+impl Info for ChangesInfo {}
+
+/// Changes come from comparing every other segment's info with what it was when last shown,
+/// which only the binary sees once they're all built, so it replaces what this builds with
+/// [`ChangesInfo::new`]. Built on its own, e.g. by `welcome2u watch`, there are no changes.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ChangesInfoBuilder {}
+
+impl InfoBuilder<ChangesInfo> for ChangesInfoBuilder {
+    #[instrument(skip(self), fields(builder_type = "ChangesInfoBuilder"))]
+    async fn build(&self) -> Result<ChangesInfo> {
+        Ok(ChangesInfo::default())
+    }
+}
+
+/// The largest unit of `seconds`, like "5h" or "3d".
+fn ago(seconds: u64) -> String {
+    match seconds {
+        0..=59 => format!("{seconds}s"),
+        60..=3599 => format!("{}m", seconds / 60),
+        3600..=86399 => format!("{}h", seconds / 3600),
+        _ => format!("{}d", seconds / 86400),
+    }
+}
+
+impl ChangesSegmentRenderer {
+    fn paragraph(&self, theme: &Theme) -> Paragraph<'_> {
+        let mut lines: Vec<Line> = self
+            .info
+            .changes
+            .iter()
+            .map(|change| Line::styled(change.text.as_str(), change.severity.style(theme)))
+            .collect();
+        if let Some(seconds) = self.info.elapsed_seconds {
+            lines.push(Line::styled(
+                format!("since last login, {} ago", ago(seconds)),
+                theme.dim,
+            ));
+        }
+        Paragraph::new(lines).wrap(Wrap { trim: true })
+    }
+}
+
+impl SegmentRenderer<ChangesInfo> for ChangesSegmentRenderer {
+    fn height(&self, width: u16, theme: &Theme) -> u16 {
+        // Nothing worth a row when nothing changed
+        if self.info.changes.is_empty() {
+            return 0;
+        }
+        paragraph_height(&self.paragraph(theme), data_width(width))
+    }
+
+    fn render(&self, frame: &mut Frame, area: Rect, theme: &Theme) -> Result<()> {
+        let [label_area, data_area, _padding] = create_label_data_layout(area);
+
+        frame.render_widget(label("Changes", theme), label_area);
+        frame.render_widget(self.paragraph(theme), data_area);
+
+        Ok(())
+    }
+
+    fn summary(&self, theme: &Theme) -> Vec<Line<'static>> {
+        let Some(severity) = self.info.changes.iter().map(|change| change.severity).max() else {
+            return Vec::new();
+        };
+        let count = self.info.changes.len();
+        vec![Line::styled(
+            format!("{count} change{}", if count == 1 { "" } else { "s" }),
+            severity.style(theme),
+        )]
+    }
+}
+
+impl From<Box<ChangesInfo>> for ChangesSegmentRenderer {
+    fn from(info: Box<ChangesInfo>) -> Self {
+        Self { info: *info }
+    }
+}
+
+pub fn register(registry: &mut Registry) {
+    registry.register("changes", |options| {
+        Ok(segment::boxed::<ChangesInfo, ChangesSegmentRenderer>(
+            options.parse::<ChangesInfoBuilder>()?,
+        ))
+    });
+}
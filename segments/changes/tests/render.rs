@@ -0,0 +1,28 @@
+use changes::{ChangesInfo, ChangesSegmentRenderer};
+use segment::testing::{render, summary};
+use segment::{assert_snapshot, fixture};
+
+#[test]
+fn renders_changes() {
+    let info: ChangesInfo = fixture!("changes");
+    assert_snapshot!(
+        "renders_changes",
+        render::<_, ChangesSegmentRenderer>(info, 60)
+    );
+}
+
+#[test]
+fn hidden_without_changes() {
+    let info = ChangesInfo::new(Vec::new(), None);
+    let buffer = render::<_, ChangesSegmentRenderer>(info, 60);
+    assert_eq!(buffer.area.height, 0);
+}
+
+#[test]
+fn summarizes_changes() {
+    let info: ChangesInfo = fixture!("changes");
+    let items = summary::<_, ChangesSegmentRenderer>(info);
+
+    let text: Vec<String> = items.iter().map(|item| item.to_string()).collect();
+    assert_eq!(text, ["3 changes"]);
+}
@@ -2,11 +2,13 @@ use anyhow::Result;
 use ratatui::prelude::*;
 use ratatui::widgets::*;
 use segment::*;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 use std::process::{Command, ExitStatus, Stdio};
 use thiserror::Error;
 use tracing::instrument;
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct CommandInfo {
     output: String,
     // command: String,
@@ -14,7 +16,8 @@ pub struct CommandInfo {
 
 impl Info for CommandInfo {}
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct CommandInfoBuilder {
     command: String,
 }
@@ -48,6 +51,34 @@ impl InfoBuilder<CommandInfo> for CommandInfoBuilder {
             output: output_str,
         })
     }
+
+    fn preflight(&self) -> Vec<Check> {
+        if self.command.is_empty() {
+            return vec![Check::new("command", CheckStatus::Missing)
+                .with_hint("Set `command` under [segment.command]")];
+        }
+
+        let check = match find_command(&self.command) {
+            Some(path) => Check::readable(path),
+            None => Check::new(self.command.as_str(), CheckStatus::Missing),
+        };
+        vec![check.with_hint(
+            "Install the command or set `command` under [segment.command] to its full path",
+        )]
+    }
+}
+
+/// Resolve `command` the way `Command::new` does: as a path when it contains a `/`,
+/// otherwise by searching `$PATH`.
+fn find_command(command: &str) -> Option<PathBuf> {
+    if command.contains('/') {
+        return Some(PathBuf::from(command));
+    }
+
+    let path = std::env::var_os("PATH")?;
+    std::env::split_paths(&path)
+        .map(|dir| dir.join(command))
+        .find(|candidate| candidate.is_file())
 }
 
 #[derive(Debug)]
@@ -68,13 +99,20 @@ pub enum CommandError {
     OutputParseError(#[from] std::string::FromUtf8Error),
 }
 
+impl CommandSegmentRenderer {
+    fn paragraph(&self) -> Paragraph<'_> {
+        // Keep indentation, which commands often use to line up their output
+        Paragraph::new(self.info.output.as_str()).wrap(Wrap { trim: false })
+    }
+}
+
 impl SegmentRenderer<CommandInfo> for CommandSegmentRenderer {
-    fn height(&self) -> u16 {
-        self.info.output.lines().count() as u16
+    fn height(&self, width: u16, _theme: &Theme) -> u16 {
+        paragraph_height(&self.paragraph(), width)
     }
 
-    fn render(&self, frame: &mut Frame, area: Rect) -> Result<()> {
-        frame.render_widget(Paragraph::new(Text::from(self.info.output.clone())), area);
+    fn render(&self, frame: &mut Frame, area: Rect, _theme: &Theme) -> Result<()> {
+        frame.render_widget(self.paragraph(), area);
         Ok(())
     }
 }
@@ -84,3 +122,11 @@ impl From<Box<CommandInfo>> for CommandSegmentRenderer {
         Self { info: *info }
     }
 }
+
+pub fn register(registry: &mut Registry) {
+    registry.register("command", |options| {
+        Ok(segment::boxed::<CommandInfo, CommandSegmentRenderer>(
+            options.parse::<CommandInfoBuilder>()?,
+        ))
+    });
+}
@@ -1,20 +1,17 @@
-use ratatui::{
-    prelude::*,
-    style::{Color, Style},
-    widgets::*,
-};
-use sysinfo::Disks;
+use ratatui::{prelude::*, widgets::*};
 
 use anyhow::Result;
 use segment::*;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
 use tracing::instrument;
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub struct DiskSegmentRenderer {
     info: DiskInfo,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct DiskInfo {
     disks: Vec<Disk>,
     warning_threshold_percent: f64,
@@ -31,13 +28,13 @@ impl Default for DiskInfo {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 struct Disk {
     name: String,
     mount_point: String,
-    free_space: u64,
-    total_space: u64,
-    used_space: u64,
+    free_bytes: u64,
+    total_bytes: u64,
+    used_bytes: u64,
 }
 
 impl Disk {
@@ -51,45 +48,159 @@ impl Disk {
     }
 
     fn used_space_formatted(&self) -> String {
-        self.format_gb(self.used_space)
+        self.format_gb(self.used_bytes)
     }
 
     fn free_space_formatted(&self) -> String {
-        self.format_gb(self.free_space)
+        self.format_gb(self.free_bytes)
     }
 
     fn total_space_formatted(&self) -> String {
-        self.format_gb(self.total_space)
+        self.format_gb(self.total_bytes)
+    }
+
+    /// Like `format_gb`, but as short as possible, e.g. "178G".
+    fn format_gb_compact(&self, value: u64) -> String {
+        let gb = value as f64 / 1_073_741_824.0;
+        if gb < 2.0 {
+            format!("{:.2}G", gb)
+        } else {
+            format!("{}G", gb.round() as u64)
+        }
+    }
+
+    /// The most detailed summary that fits in `width` columns: without the device name if the
+    /// full one doesn't fit, then with compact units, then with only the free space, and finally
+    /// with the start of the mount point cut off.
+    fn summary(&self, width: usize, free_style: Style) -> Line<'static> {
+        let line = |prefix: String, free: &str| {
+            Line::from(vec![
+                Span::raw(prefix),
+                Span::styled(free.to_string(), free_style),
+                Span::raw(")"),
+            ])
+        };
+
+        let free = format!("{} free", self.free_space_formatted());
+        let used = self.used_space_formatted();
+        let total = self.total_space_formatted();
+        let compact_free = format!("{} free", self.format_gb_compact(self.free_bytes));
+        let compact_used = self.format_gb_compact(self.used_bytes);
+        let compact_total = self.format_gb_compact(self.total_bytes);
+        let mount_point = &self.mount_point;
+        let candidates = [
+            line(
+                format!(
+                    "{} ({mount_point}) - {used} used / {total} total (",
+                    self.name
+                ),
+                &free,
+            ),
+            line(
+                format!("{mount_point} - {used} used / {total} total ("),
+                &free,
+            ),
+            line(
+                format!("{mount_point} {compact_used}/{compact_total} ("),
+                &compact_free,
+            ),
+            line(format!("{mount_point} ("), &compact_free),
+        ];
+        if let Some(summary) = candidates.into_iter().find(|line| line.width() <= width) {
+            return summary;
+        }
+
+        // The end of a mount point usually says the most about it
+        let room = width.saturating_sub(compact_free.len() + 3);
+        let tail: String = mount_point
+            .chars()
+            .rev()
+            .take(room.saturating_sub(1))
+            .collect();
+        let mount_point = format!("…{}", tail.chars().rev().collect::<String>());
+        line(format!("{mount_point} ("), &compact_free)
     }
 
     fn percent_used(&self) -> f64 {
-        self.used_space as f64 / self.total_space as f64 * 100.0
+        // Some volumes (e.g. empty card readers) report no size at all
+        if self.total_bytes == 0 {
+            return 0.0;
+        }
+        self.used_bytes as f64 / self.total_bytes as f64 * 100.0
+    }
+}
+
+/// Smallest growth or shrinkage of a disk worth calling out in the `changes` segment.
+const NOTABLE_CHANGE_BYTES: u64 = 1_073_741_824;
+
+impl DiskInfo {
+    fn severity(&self, used_percentage: f64) -> Severity {
+        Severity::from_thresholds(
+            used_percentage,
+            self.warning_threshold_percent,
+            self.critical_threshold_percent,
+        )
     }
 }
 
-impl Info for DiskInfo {}
+impl Info for DiskInfo {
+    fn changes(&self, previous: &Self, _elapsed: Duration) -> Vec<Change> {
+        let mut changes = Vec::new();
+        for disk in &self.disks {
+            let Some(before) = previous
+                .disks
+                .iter()
+                .find(|before| before.mount_point == disk.mount_point)
+            else {
+                changes.push(Change::notice(format!("{} mounted", disk.mount_point)));
+                continue;
+            };
+
+            let difference = disk.used_bytes.abs_diff(before.used_bytes);
+            if difference < NOTABLE_CHANGE_BYTES {
+                continue;
+            }
+            let size = disk.format_gb(difference);
+            changes.push(if disk.used_bytes > before.used_bytes {
+                Change::new(
+                    format!("{} grew {size}", disk.mount_point),
+                    self.severity(disk.percent_used()),
+                )
+            } else {
+                Change::notice(format!("{} freed {size}", disk.mount_point))
+            });
+        }
+
+        for before in &previous.disks {
+            if !self
+                .disks
+                .iter()
+                .any(|disk| disk.mount_point == before.mount_point)
+            {
+                changes.push(Change::warning(format!("{} unmounted", before.mount_point)));
+            }
+        }
+        changes
+    }
+}
 
-#[derive(Debug)]
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct DiskInfoBuilder {
     excluded_mount_points: Vec<String>,
     warning_threshold_percent: f64,
     critical_threshold_percent: f64,
+    #[serde(skip)]
+    system: SharedSystem,
 }
 
 impl Default for DiskInfoBuilder {
     fn default() -> Self {
         Self {
-            excluded_mount_points: Vec::new(),
+            excluded_mount_points: vec!["/System/Volumes/Data".to_string()],
             warning_threshold_percent: 85.0,
             critical_threshold_percent: 95.0,
-        }
-    }
-}
-
-impl Default for DiskSegmentRenderer {
-    fn default() -> Self {
-        Self {
-            info: DiskInfo::default(),
+            system: SharedSystem::default(),
         }
     }
 }
@@ -109,37 +220,27 @@ impl DiskInfoBuilder {
         self.critical_threshold_percent = percent;
         self
     }
+
+    pub fn system(mut self, system: SharedSystem) -> Self {
+        self.system = system;
+        self
+    }
 }
 
 impl InfoBuilder<DiskInfo> for DiskInfoBuilder {
     #[instrument(skip(self), fields(builder_type = "DiskInfoBuilder"))]
     async fn build(&self) -> Result<DiskInfo> {
-        let disks = Disks::new_with_refreshed_list();
-
-        let disks = disks
+        let disks = self
+            .system
+            .disks()
             .into_iter()
-            .filter_map(|disk| {
-                if self
-                    .excluded_mount_points
-                    .contains(&disk.mount_point().to_str().unwrap().to_string())
-                {
-                    return None;
-                }
-
-                let name = disk.name().to_str().unwrap().to_string();
-                let mount_point = disk.mount_point().to_str().unwrap().to_string();
-
-                let free_space = disk.available_space();
-                let total_space = disk.total_space();
-                let used_space = total_space - free_space;
-
-                Some(Disk {
-                    name,
-                    mount_point,
-                    free_space,
-                    total_space,
-                    used_space,
-                })
+            .filter(|disk| !self.excluded_mount_points.contains(&disk.mount_point))
+            .map(|disk| Disk {
+                free_bytes: disk.available_bytes,
+                total_bytes: disk.total_bytes,
+                used_bytes: disk.total_bytes.saturating_sub(disk.available_bytes),
+                name: disk.name,
+                mount_point: disk.mount_point,
             })
             .collect();
 
@@ -149,17 +250,22 @@ impl InfoBuilder<DiskInfo> for DiskInfoBuilder {
             critical_threshold_percent: self.critical_threshold_percent,
         })
     }
+
+    fn refresh_interval(&self) -> Option<Duration> {
+        Some(Duration::from_secs(30))
+    }
 }
 
 impl SegmentRenderer<DiskInfo> for DiskSegmentRenderer {
-    fn height(&self) -> u16 {
+    fn height(&self, _width: u16, _theme: &Theme) -> u16 {
+        // Summaries are abbreviated to fit rather than wrapped
         (self.info.disks.len() * 2) as u16
     }
 
-    fn render(&self, frame: &mut Frame, area: Rect) -> Result<()> {
+    fn render(&self, frame: &mut Frame, area: Rect, theme: &Theme) -> Result<()> {
         let [label_area, data_area, _padding] = create_label_data_layout(area);
 
-        frame.render_widget(label("Disk"), label_area);
+        frame.render_widget(label("Disk", theme), label_area);
 
         let chunks = Layout::default()
             .direction(Direction::Vertical)
@@ -176,35 +282,20 @@ impl SegmentRenderer<DiskInfo> for DiskSegmentRenderer {
             let used_percentage = disk.percent_used();
             let free_percentage = 100.0 - used_percentage;
 
-            let usage_color = if used_percentage >= self.info.critical_threshold_percent {
-                Color::Red
-            } else if used_percentage >= self.info.warning_threshold_percent {
-                Color::Yellow
-            } else {
-                Color::Green
-            };
+            let usage_style = theme.status(
+                used_percentage,
+                self.info.warning_threshold_percent,
+                self.info.critical_threshold_percent,
+            );
 
-            let summary = Line::from(vec![
-                Span::raw(format!(
-                    "{} ({}) - {} used / {} total (",
-                    disk.name,
-                    disk.mount_point,
-                    disk.used_space_formatted(),
-                    disk.total_space_formatted()
-                )),
-                Span::styled(
-                    format!("{} free", disk.free_space_formatted()),
-                    Style::default().fg(usage_color),
-                ),
-                Span::raw(")"),
-            ]);
+            let summary = disk.summary(chunk.width as usize, usage_style);
 
             frame.render_widget(
                 LineGauge::default()
                     .block(Block::default().title(summary))
-                    .filled_style(Style::default().fg(Color::Red).add_modifier(Modifier::BOLD))
-                    .unfilled_style(Style::default().fg(Color::Green))
-                    .line_set(symbols::line::THICK)
+                    .filled_style(theme.gauge_filled)
+                    .unfilled_style(theme.gauge_unfilled)
+                    .line_set(theme.gauge_line)
                     .ratio(free_percentage / 100.0),
                 *chunk,
             );
@@ -212,6 +303,22 @@ impl SegmentRenderer<DiskInfo> for DiskSegmentRenderer {
 
         Ok(())
     }
+
+    fn summary(&self, theme: &Theme) -> Vec<Line<'static>> {
+        self.info
+            .disks
+            .iter()
+            .map(|disk| {
+                let used_percentage = disk.percent_used();
+                let style = theme.status(
+                    used_percentage,
+                    self.info.warning_threshold_percent,
+                    self.info.critical_threshold_percent,
+                );
+                summary_item(&disk.mount_point, format!("{:.0}%", used_percentage), style)
+            })
+            .collect()
+    }
 }
 
 impl From<Box<DiskInfo>> for DiskSegmentRenderer {
@@ -219,3 +326,10 @@ impl From<Box<DiskInfo>> for DiskSegmentRenderer {
         Self { info: *info }
     }
 }
+
+pub fn register(registry: &mut Registry) {
+    registry.register("disk", |options| {
+        let builder = options.parse::<DiskInfoBuilder>()?.system(options.system());
+        Ok(segment::boxed::<DiskInfo, DiskSegmentRenderer>(builder))
+    });
+}
@@ -0,0 +1,22 @@
+use disk::{DiskInfoBuilder, DiskSegmentRenderer};
+use segment::testing::render;
+use segment::{assert_snapshot, InfoBuilder, SharedSystem, SystemSnapshot};
+use std::path::Path;
+
+fn system(name: &str) -> SharedSystem {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name);
+    SharedSystem::new(SystemSnapshot::load(&path).unwrap())
+}
+
+#[tokio::test]
+async fn builds_from_captured_system() {
+    let builder = DiskInfoBuilder::default().system(system("system.toml"));
+    let info = builder.build().await.unwrap();
+
+    assert_snapshot!(
+        "builds_from_captured_system",
+        render::<_, DiskSegmentRenderer>(info, 80)
+    );
+}
@@ -0,0 +1,18 @@
+use disk::DiskInfo;
+use segment::{fixture, Change, Info};
+use std::time::Duration;
+
+#[test]
+fn reports_growth_and_mounts() {
+    let earlier: DiskInfo = fixture!("disks-earlier");
+    let now: DiskInfo = fixture!("disks");
+
+    assert_eq!(
+        now.changes(&earlier, Duration::from_secs(86400)),
+        [
+            Change::warning("/ grew 12 GB"),
+            Change::notice("/Volumes/Scratch mounted"),
+            Change::warning("/Volumes/Install unmounted"),
+        ]
+    );
+}
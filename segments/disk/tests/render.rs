@@ -0,0 +1,35 @@
+use disk::{DiskInfo, DiskSegmentRenderer};
+use ratatui::style::Color;
+use segment::testing::{render, summary};
+use segment::{assert_snapshot, fixture};
+
+#[test]
+fn renders_usage_bars() {
+    let info: DiskInfo = fixture!("disks");
+    assert_snapshot!(
+        "renders_usage_bars",
+        render::<_, DiskSegmentRenderer>(info, 60)
+    );
+}
+
+#[test]
+fn narrow_terminal() {
+    let info: DiskInfo = fixture!("disks");
+    assert_snapshot!(
+        "narrow_terminal",
+        render::<_, DiskSegmentRenderer>(info, 40)
+    );
+}
+
+#[test]
+fn summarizes_each_disk() {
+    let info: DiskInfo = fixture!("disks");
+    let items = summary::<_, DiskSegmentRenderer>(info);
+
+    let text: Vec<String> = items.iter().map(|item| item.to_string()).collect();
+    assert_eq!(
+        text,
+        ["/ 80%", "/Volumes/Backup 97%", "/Volumes/Scratch 40%"]
+    );
+    assert_eq!(items[1].spans[1].style.fg, Some(Color::Red));
+}
@@ -8,28 +8,83 @@ use chrono_humanize::{Accuracy, HumanTime, Tense};
 use iso8601_timestamp::Timestamp;
 use ratatui::{prelude::*, widgets::*};
 use segment::*;
+use serde::{Deserialize, Serialize};
 use std::default::Default;
+use std::time::Duration;
 use tracing::instrument;
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct DockerInfo {
     status: DockerStatus,
     containers: Vec<ContainerInfo>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum DockerStatus {
     Running,
     Unavailable(String),
 }
 
-impl Info for DockerInfo {}
+impl Info for DockerInfo {
+    fn changes(&self, previous: &Self, elapsed: Duration) -> Vec<Change> {
+        // Without a connection there's nothing to compare, and the segment already says why
+        let (DockerStatus::Running, DockerStatus::Running) = (&self.status, &previous.status)
+        else {
+            return Vec::new();
+        };
+
+        let running =
+            |container: &ContainerInfo| container.status == ContainerStateStatusEnum::RUNNING;
+        let mut changes = Vec::new();
+        for container in &self.containers {
+            let name = &container.name;
+            let before = previous
+                .containers
+                .iter()
+                .find(|before| before.name == container.name);
+            match before {
+                None if running(container) => {
+                    changes.push(Change::notice(format!("container {name} started")))
+                }
+                None => changes.push(Change::notice(format!("container {name} created"))),
+                Some(before) if running(before) && !running(container) => {
+                    changes.push(match container.exit_code {
+                        0 => Change::warning(format!("container {name} exited")),
+                        code => Change::critical(format!("container {name} exited with {code}")),
+                    })
+                }
+                Some(before) if !running(before) && running(container) => {
+                    changes.push(Change::notice(format!("container {name} started")))
+                }
+                // Running both times, but started since
+                Some(_)
+                    if running(container) && container.duration_seconds < elapsed.as_secs_f64() =>
+                {
+                    changes.push(Change::warning(format!("container {name} restarted")))
+                }
+                Some(_) => {}
+            }
+        }
+
+        for before in &previous.containers {
+            if !self
+                .containers
+                .iter()
+                .any(|container| container.name == before.name)
+            {
+                changes.push(Change::notice(format!("container {} removed", before.name)));
+            }
+        }
+        changes
+    }
+}
 
 #[derive(Debug)]
 pub struct DockerSegmentRenderer {
     info: DockerInfo,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 struct ContainerInfo {
     name: String,
     status: ContainerStateStatusEnum,
@@ -37,10 +92,34 @@ struct ContainerInfo {
     duration_seconds: f64,
 }
 
-#[derive(Debug, Default)]
-pub struct DockerInfoBuilder;
+/// Where Docker listens when neither `socket` nor `$DOCKER_HOST` says otherwise.
+const DEFAULT_SOCKET: &str = "unix:///var/run/docker.sock";
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DockerInfoBuilder {
+    /// Docker's socket, like `unix:///var/run/docker.sock`; `$DOCKER_HOST` or that when unset.
+    socket: Option<String>,
+}
 
 impl DockerInfoBuilder {
+    pub fn socket(mut self, socket: Option<String>) -> Self {
+        self.socket = socket;
+        self
+    }
+
+    /// The address to reach Docker at: `socket`, else `$DOCKER_HOST`, else the usual socket.
+    fn address(&self) -> String {
+        self.socket
+            .clone()
+            .or_else(|| {
+                std::env::var("DOCKER_HOST")
+                    .ok()
+                    .filter(|host| !host.is_empty())
+            })
+            .unwrap_or_else(|| DEFAULT_SOCKET.to_string())
+    }
+
     fn duration_since(seconds: &f64) -> String {
         let dt = chrono::Duration::seconds(seconds.round() as i64);
         let ht = HumanTime::from(dt);
@@ -86,11 +165,12 @@ impl DockerInfoBuilder {
 impl InfoBuilder<DockerInfo> for DockerInfoBuilder {
     #[instrument(skip(self), fields(builder_type = "DockerInfoBuilder"))]
     async fn build(&self) -> Result<DockerInfo> {
-        match Docker::connect_with_socket(
-            "unix:///Users/josh.nichols/.colima/gusto/docker.sock",
-            5,
-            API_DEFAULT_VERSION,
-        ) {
+        let docker = match &self.socket {
+            Some(socket) => Docker::connect_with_socket(socket, 5, API_DEFAULT_VERSION),
+            // Honors $DOCKER_HOST, over TCP too
+            None => Docker::connect_with_local_defaults(),
+        };
+        match docker {
             Ok(docker) => {
                 let options = ListContainersOptions::<String> {
                     all: true,
@@ -132,6 +212,20 @@ impl InfoBuilder<DockerInfo> for DockerInfoBuilder {
             }),
         }
     }
+
+    fn preflight(&self) -> Vec<Check> {
+        // Only unix sockets can be checked without talking to Docker
+        match self.address().strip_prefix("unix://") {
+            Some(path) => vec![Check::exists(path).with_hint(
+                "Start Docker (e.g. `colima start`), or point `socket` under [segment.docker] or $DOCKER_HOST at it",
+            )],
+            None => Vec::new(),
+        }
+    }
+
+    fn refresh_interval(&self) -> Option<Duration> {
+        Some(Duration::from_secs(10))
+    }
 }
 
 impl DockerSegmentRenderer {
@@ -156,18 +250,25 @@ impl DockerSegmentRenderer {
 }
 
 impl SegmentRenderer<DockerInfo> for DockerSegmentRenderer {
-    fn height(&self) -> u16 {
-        self.info
-            .containers
-            .iter()
-            .filter(|c| self.is_container_visible(c))
-            .count() as u16
+    fn height(&self, width: u16, _theme: &Theme) -> u16 {
+        match &self.info.status {
+            DockerStatus::Running => self
+                .info
+                .containers
+                .iter()
+                .filter(|c| self.is_container_visible(c))
+                .count() as u16,
+            DockerStatus::Unavailable(message) => paragraph_height(
+                &Paragraph::new(message.as_str()).wrap(Wrap { trim: true }),
+                data_width(width),
+            ),
+        }
     }
 
-    fn render(&self, frame: &mut Frame, area: Rect) -> Result<()> {
+    fn render(&self, frame: &mut Frame, area: Rect, theme: &Theme) -> Result<()> {
         let chunks = create_label_data_layout(area);
 
-        frame.render_widget(label("Docker"), chunks[0]);
+        frame.render_widget(label("Docker", theme), chunks[0]);
 
         match &self.info.status {
             DockerStatus::Running => {
@@ -178,27 +279,27 @@ impl SegmentRenderer<DockerInfo> for DockerSegmentRenderer {
                     .filter(|c| self.is_container_visible(c))
                     .collect();
 
+                // Long names are cut short so there's always room for the status
                 let max_name_width = active_containers
                     .iter()
                     .map(|container| container.name.len())
                     .max()
                     .unwrap_or(0)
+                    .min(chunks[1].width as usize / 2)
                     + 1; // +1 for the colon
 
                 let rows: Vec<Row> = active_containers
                     .iter()
                     .filter_map(|container| {
                         let status_style = match container.status {
-                            ContainerStateStatusEnum::RUNNING => {
-                                Some(Style::default().fg(Color::Green))
-                            }
+                            ContainerStateStatusEnum::RUNNING => Some(theme.ok),
                             ContainerStateStatusEnum::EXITED => {
                                 if let Some(hours) = self.get_hours_since_exit(container) {
                                     if hours > 8.0 {
                                         return None;
                                     }
                                 }
-                                Some(Style::default().fg(Color::Red))
+                                Some(theme.critical)
                             }
                             _ => Some(Style::default()),
                         };
@@ -223,7 +324,7 @@ impl SegmentRenderer<DockerInfo> for DockerSegmentRenderer {
                         Some(Row::new(vec![
                             Cell::from(format!(
                                 "{:>width$}:",
-                                container.name,
+                                truncate(&container.name, max_name_width - 1),
                                 width = max_name_width - 1
                             )),
                             Cell::from(status_text).style(status_style.unwrap_or_default()),
@@ -232,7 +333,7 @@ impl SegmentRenderer<DockerInfo> for DockerSegmentRenderer {
                     .collect();
 
                 let table = Table::new(rows, &[])
-                    .widths(&[
+                    .widths([
                         Constraint::Length(max_name_width as u16),
                         Constraint::Percentage(100),
                     ])
@@ -242,7 +343,9 @@ impl SegmentRenderer<DockerInfo> for DockerSegmentRenderer {
             }
             DockerStatus::Unavailable(message) => {
                 frame.render_widget(
-                    Paragraph::new(message.as_str()).style(Style::default().fg(Color::Red)),
+                    Paragraph::new(message.as_str())
+                        .wrap(Wrap { trim: true })
+                        .style(theme.critical),
                     chunks[1],
                 );
             }
@@ -250,6 +353,33 @@ impl SegmentRenderer<DockerInfo> for DockerSegmentRenderer {
 
         Ok(())
     }
+
+    fn summary(&self, theme: &Theme) -> Vec<Line<'static>> {
+        if !matches!(self.info.status, DockerStatus::Running) {
+            return Vec::new();
+        }
+
+        let count = |status: ContainerStateStatusEnum| {
+            self.info
+                .containers
+                .iter()
+                .filter(|container| {
+                    container.status == status && self.is_container_visible(container)
+                })
+                .count()
+        };
+        let running = count(ContainerStateStatusEnum::RUNNING);
+        let exited = count(ContainerStateStatusEnum::EXITED);
+
+        let mut items = vec![Line::from(format!(
+            "{running} container{}",
+            if running == 1 { "" } else { "s" }
+        ))];
+        if exited > 0 {
+            items.push(Line::styled(format!("{exited} exited"), theme.critical));
+        }
+        items
+    }
 }
 
 impl From<Box<DockerInfo>> for DockerSegmentRenderer {
@@ -257,3 +387,11 @@ impl From<Box<DockerInfo>> for DockerSegmentRenderer {
         Self { info: *info }
     }
 }
+
+pub fn register(registry: &mut Registry) {
+    registry.register("docker", |options| {
+        Ok(segment::boxed::<DockerInfo, DockerSegmentRenderer>(
+            options.parse::<DockerInfoBuilder>()?,
+        ))
+    });
+}
@@ -0,0 +1,27 @@
+use docker::DockerInfo;
+use segment::{fixture, Change, Info};
+use std::time::Duration;
+
+#[test]
+fn reports_exited_and_removed_containers() {
+    let earlier: DockerInfo = fixture!("containers-earlier");
+    let now: DockerInfo = fixture!("containers");
+
+    assert_eq!(
+        now.changes(&earlier, Duration::from_secs(7200)),
+        [
+            Change::critical("container redis exited with 137"),
+            Change::notice("container worker removed"),
+        ]
+    );
+}
+
+#[test]
+fn reports_restarts() {
+    let earlier: DockerInfo = fixture!("containers-earlier");
+    let now: DockerInfo = fixture!("containers");
+
+    // postgres has been up 26 hours, so it restarted if the MOTD was last shown before that
+    let changes = now.changes(&earlier, Duration::from_secs(2 * 86400));
+    assert_eq!(changes[0], Change::warning("container postgres restarted"));
+}
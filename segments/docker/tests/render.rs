@@ -0,0 +1,34 @@
+use docker::{DockerInfo, DockerSegmentRenderer};
+use segment::testing::{render, summary};
+use segment::{assert_snapshot, fixture};
+
+#[test]
+fn renders_containers() {
+    let info: DockerInfo = fixture!("containers");
+    assert_snapshot!(
+        "renders_containers",
+        render::<_, DockerSegmentRenderer>(info, 80)
+    );
+}
+
+#[test]
+fn renders_unavailable() {
+    let info: DockerInfo = fixture!("unavailable");
+    assert_snapshot!(
+        "renders_unavailable",
+        render::<_, DockerSegmentRenderer>(info, 80)
+    );
+}
+
+#[test]
+fn summarizes_containers() {
+    let info: DockerInfo = fixture!("containers");
+    let text: Vec<String> = summary::<_, DockerSegmentRenderer>(info)
+        .iter()
+        .map(|item| item.to_string())
+        .collect();
+    assert_eq!(text, ["1 container", "2 exited"]);
+
+    let info: DockerInfo = fixture!("unavailable");
+    assert!(summary::<_, DockerSegmentRenderer>(info).is_empty());
+}
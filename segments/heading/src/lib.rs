@@ -1,21 +1,18 @@
 use anyhow::Result;
 use figlet_rs::FIGfont;
-use rand::{seq::SliceRandom, thread_rng};
+use rand::{seq::SliceRandom, Rng};
 use ratatui::{prelude::*, widgets::*, Frame};
 use segment::*;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use ansi_to_tui::IntoText;
-use fortune::{Fortunes, NoFortunesError};
+use fortune::{Filter, Fortunes, Offensive, Options, SearchPath, Selection, Source};
 use lolcrab::Lolcrab;
+use std::path::PathBuf;
 
-fn choose_fortune() -> Result<String, NoFortunesError> {
-    let fortune_path = String::from("/opt/homebrew/opt/fortune/share/games/fortunes/intro");
-    let fortune_file = Fortunes::from_file(&fortune_path).unwrap();
-    let fortune = fortune_file.choose_one()?;
-
-    Ok(fortune.to_string())
-}
+/// State file remembering recent headings, for `selection = "no-repeat"`.
+const HISTORY_FILE: &str = "heading-history.json";
 
 #[derive(Error, Debug)]
 pub enum FigletError {
@@ -23,11 +20,12 @@ pub enum FigletError {
     FontLoadError { path: String, message: String },
     #[error("Could not convert text to figlet: {message}")]
     ConversionError { message: String },
+    #[error("No figlet fonts configured")]
+    NoFonts,
 }
 
-fn figlet(font: String, message: &str) -> Result<String> {
-    let font_directory = "/opt/homebrew/opt/figlet";
-    let font_path = format!("{}/share/figlet/fonts/{}.flf", font_directory, font);
+fn figlet(font_directory: &str, font: &str, message: &str) -> Result<String> {
+    let font_path = format!("{}/{}.flf", font_directory, font);
 
     let font =
         FIGfont::from_file(font_path.as_str()).map_err(|error| FigletError::FontLoadError {
@@ -46,24 +44,12 @@ fn figlet(font: String, message: &str) -> Result<String> {
     Ok(trimmed_figure)
 }
 
-fn random_font() -> String {
-    let fonts = [
-        "bell",
-        // "big", # seems broken from figlet-rs code?
-        "slant",
-        "contessa",
-        "computer",
-        "cricket",
-        "cybermedium",
-        "jazmine",
-        "rectangles",
-    ];
-    let mut rng = thread_rng();
-    let font_choice = fonts.choose(&mut rng);
-    font_choice.unwrap().to_string()
+fn random_font(fonts: &[String], rng: &mut impl Rng) -> Result<String, FigletError> {
+    let font_choice = fonts.choose(rng).ok_or(FigletError::NoFonts)?;
+    Ok(font_choice.to_string())
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct HeadingInfo {
     pub heading: String,
     pub figure: String,
@@ -72,19 +58,164 @@ pub struct HeadingInfo {
 
 impl Info for HeadingInfo {}
 
-#[derive(Debug, Default)]
-pub struct HeadingSegmentInfoBuilder {}
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HeadingSegmentInfoBuilder {
+    /// A fortune database's name, like `intro`, or its path.
+    fortune: String,
+    /// Directories to look for the database in before `$FORTUNE_PATH` and the usual places.
+    fortune_path: Vec<PathBuf>,
+    /// Whether to take headings from the offensive databases too, or only from them.
+    offensive: Offensive,
+    /// Which fortunes to choose from, by length and pattern.
+    #[serde(flatten)]
+    filter: Filter,
+    /// A new heading, and font, at random every time, the same all day, or none of the
+    /// recent headings.
+    selection: Selection,
+    #[serde(skip)]
+    seed: Option<u64>,
+    #[serde(skip)]
+    history: Option<PathBuf>,
+    font_directory: String,
+    fonts: Vec<String>,
+}
+
+impl Default for HeadingSegmentInfoBuilder {
+    fn default() -> Self {
+        Self {
+            fortune: "intro".to_string(),
+            fortune_path: Vec::new(),
+            offensive: Offensive::default(),
+            filter: Filter::default(),
+            selection: Selection::default(),
+            seed: None,
+            history: None,
+            font_directory: "/opt/homebrew/opt/figlet/share/figlet/fonts".to_string(),
+            fonts: [
+                "bell",
+                // "big", # seems broken from figlet-rs code?
+                "slant",
+                "contessa",
+                "computer",
+                "cricket",
+                "cybermedium",
+                "jazmine",
+                "rectangles",
+            ]
+            .map(String::from)
+            .to_vec(),
+        }
+    }
+}
+
+impl HeadingSegmentInfoBuilder {
+    pub fn fortune(mut self, fortune: String) -> Self {
+        self.fortune = fortune;
+        self
+    }
+
+    pub fn fortune_path(mut self, fortune_path: Vec<PathBuf>) -> Self {
+        self.fortune_path = fortune_path;
+        self
+    }
+
+    pub fn offensive(mut self, offensive: Offensive) -> Self {
+        self.offensive = offensive;
+        self
+    }
+
+    pub fn filter(mut self, filter: Filter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    pub fn selection(mut self, selection: Selection) -> Self {
+        self.selection = selection;
+        self
+    }
+
+    pub fn seed(mut self, seed: Option<u64>) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    pub fn history(mut self, history: Option<PathBuf>) -> Self {
+        self.history = history;
+        self
+    }
+
+    pub fn font_directory(mut self, font_directory: String) -> Self {
+        self.font_directory = font_directory;
+        self
+    }
+
+    pub fn fonts(mut self, fonts: Vec<String>) -> Self {
+        self.fonts = fonts;
+        self
+    }
+
+    fn options(&self) -> Options {
+        Options {
+            offensive: self.offensive,
+            filter: self.filter.clone(),
+            ..Options::default()
+        }
+    }
+
+    fn search_path(&self) -> SearchPath {
+        SearchPath::from_env().with_dirs_first(self.fortune_path.iter().cloned())
+    }
+}
 
 impl InfoBuilder<HeadingInfo> for HeadingSegmentInfoBuilder {
     async fn build(&self) -> Result<HeadingInfo> {
-        let heading = choose_fortune()?;
-        let figure = figlet(random_font(), &heading)?;
+        let source = Source {
+            name: self.fortune.clone(),
+            percent: None,
+        };
+        let fortunes = Fortunes::load(&[source], &self.search_path(), &self.options())?;
+        let heading = fortunes.choose(self.selection, self.seed, self.history.as_deref())?;
+        let font_choice = random_font(&self.fonts, &mut self.selection.rng(self.seed))?;
+        let figure = figlet(&self.font_directory, &font_choice, &heading)?;
         Ok(HeadingInfo {
             heading,
             figure,
-            font_choice: random_font(),
+            font_choice,
         })
     }
+
+    fn preflight(&self) -> Vec<Check> {
+        let mut checks = self
+            .search_path()
+            .checks(&self.fortune, self.offensive, "heading");
+
+        if self.fonts.is_empty() {
+            checks.push(
+                Check::new(
+                    "fonts",
+                    CheckStatus::Failed(FigletError::NoFonts.to_string()),
+                )
+                .with_hint("List at least one font in `fonts` under [segment.heading]"),
+            );
+        }
+        let font_directory = Check::exists(&self.font_directory).with_hint(
+            "Install figlet (`brew install figlet` or `apt install figlet`) or set `font_directory` under [segment.heading]",
+        );
+        if !font_directory.passed() {
+            // Every font would be missing too, so don't list them one by one
+            checks.push(font_directory);
+            return checks;
+        }
+        for font in &self.fonts {
+            checks.push(
+                Check::readable(format!("{}/{}.flf", self.font_directory, font))
+                    .with_hint("Remove the font from `fonts` under [segment.heading]"),
+            );
+        }
+
+        checks
+    }
 }
 
 #[derive(Debug)]
@@ -92,21 +223,53 @@ pub struct HeadingSegmentRenderer {
     pub info: HeadingInfo,
 }
 
+impl HeadingSegmentRenderer {
+    /// The figure, or the plain heading when the figure is wider than `width` and would be cut.
+    fn text(&self, width: u16) -> &str {
+        let widest = self
+            .info
+            .figure
+            .lines()
+            .map(|line| Line::raw(line).width())
+            .max()
+            .unwrap_or_default();
+        if widest <= usize::from(width) {
+            &self.info.figure
+        } else {
+            &self.info.heading
+        }
+    }
+}
+
+fn paragraph(text: Text<'_>) -> Paragraph<'_> {
+    // Add 1 line padding above and 2 below
+    Paragraph::new(text)
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: false })
+        .block(Block::default().padding(Padding::new(0, 0, 1, 2)))
+}
+
 impl SegmentRenderer<HeadingInfo> for HeadingSegmentRenderer {
-    fn height(&self) -> u16 {
-        // Add 1 line padding above and 2 below
-        self.info.figure.lines().count() as u16 + 3
+    fn height(&self, width: u16, _theme: &Theme) -> u16 {
+        paragraph_height(&paragraph(Text::raw(self.text(width))), width)
     }
 
-    fn render(&self, frame: &mut Frame, area: Rect) -> Result<()> {
-        let mut colorized_figure = Vec::new();
-        Lolcrab::new(None, None).colorize_str(&self.info.figure, &mut colorized_figure)?;
+    fn full_width(&self) -> bool {
+        true
+    }
 
-        let paragraph = Paragraph::new(colorized_figure.into_text()?)
-            .alignment(Alignment::Center)
-            .block(Block::default().padding(Padding::new(0, 0, 1, 2)));
+    fn render(&self, frame: &mut Frame, area: Rect, theme: &Theme) -> Result<()> {
+        let text = self.text(area.width);
+        let figure = match theme.heading {
+            Some(style) => Text::styled(text, style),
+            None => {
+                let mut colorized_figure = Vec::new();
+                Lolcrab::new(None, None).colorize_str(text, &mut colorized_figure)?;
+                colorized_figure.into_text()?
+            }
+        };
 
-        frame.render_widget(paragraph, area);
+        frame.render_widget(paragraph(figure), area);
 
         Ok(())
     }
@@ -117,3 +280,15 @@ impl From<Box<HeadingInfo>> for HeadingSegmentRenderer {
         Self { info: *info }
     }
 }
+
+pub fn register(registry: &mut Registry) {
+    registry.register("heading", |options| {
+        let builder = options
+            .parse::<HeadingSegmentInfoBuilder>()?
+            .seed(options.seed())
+            .history(options.state_dir().map(|dir| dir.join(HISTORY_FILE)));
+        Ok(segment::boxed::<HeadingInfo, HeadingSegmentRenderer>(
+            builder,
+        ))
+    });
+}
@@ -2,16 +2,19 @@ use anyhow::Result;
 use local_ip_address::local_ip;
 use ratatui::{prelude::*, widgets::*};
 use segment::*;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
 use tracing::instrument;
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct IpInfo {
     ip_address: String,
 }
 
 impl Info for IpInfo {}
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct IpInfoBuilder {}
 
 impl InfoBuilder<IpInfo> for IpInfoBuilder {
@@ -21,6 +24,19 @@ impl InfoBuilder<IpInfo> for IpInfoBuilder {
         let ip_address = ip.to_string();
         Ok(IpInfo { ip_address })
     }
+
+    fn preflight(&self) -> Vec<Check> {
+        let check = match local_ip() {
+            Ok(_) => Check::ok("local IP address"),
+            Err(error) => Check::new("local IP address", CheckStatus::Failed(error.to_string()))
+                .with_hint("Connect to a network"),
+        };
+        vec![check]
+    }
+
+    fn refresh_interval(&self) -> Option<Duration> {
+        Some(Duration::from_secs(60))
+    }
 }
 
 #[derive(Debug)]
@@ -29,14 +45,14 @@ pub struct IpSegmentRenderer {
 }
 
 impl SegmentRenderer<IpInfo> for IpSegmentRenderer {
-    fn height(&self) -> u16 {
+    fn height(&self, _width: u16, _theme: &Theme) -> u16 {
         1
     }
 
-    fn render(&self, frame: &mut Frame, area: Rect) -> Result<()> {
+    fn render(&self, frame: &mut Frame, area: Rect, theme: &Theme) -> Result<()> {
         let [label_area, data_area, _padding] = create_label_data_layout(area);
 
-        frame.render_widget(label("IP"), label_area);
+        frame.render_widget(label("IP", theme), label_area);
         frame.render_widget(Paragraph::new(self.info.ip_address.clone()), data_area);
 
         Ok(())
@@ -48,3 +64,11 @@ impl From<Box<IpInfo>> for IpSegmentRenderer {
         Self { info: *info }
     }
 }
+
+pub fn register(registry: &mut Registry) {
+    registry.register("ip", |options| {
+        Ok(segment::boxed::<IpInfo, IpSegmentRenderer>(
+            options.parse::<IpInfoBuilder>()?,
+        ))
+    });
+}
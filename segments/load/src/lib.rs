@@ -1,7 +1,8 @@
 use anyhow::Result;
 use ratatui::{prelude::*, widgets::*};
 use segment::*;
-use sysinfo::{LoadAvg, System};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
 use tracing::instrument;
 
 #[derive(Default, Debug)]
@@ -9,53 +10,62 @@ pub struct LoadSegmentRenderer {
     info: LoadInfo,
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct LoadInfo {
-    loads: LoadAvg,
+    loads: LoadAverage,
     cores: usize,
 }
 
 impl Info for LoadInfo {}
 
-#[derive(Debug, Default)]
-pub struct LoadInfoBuilder {}
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LoadInfoBuilder {
+    #[serde(skip)]
+    system: SharedSystem,
+}
+
+impl LoadInfoBuilder {
+    pub fn system(mut self, system: SharedSystem) -> Self {
+        self.system = system;
+        self
+    }
+}
 
 impl InfoBuilder<LoadInfo> for LoadInfoBuilder {
     #[instrument(skip(self), fields(builder_type = "LoadInfoBuilder"))]
     async fn build(&self) -> Result<LoadInfo> {
-        let mut sys = System::new_all();
-        sys.refresh_all();
-
-        let loads = System::load_average();
-        let cores = sys.physical_core_count().unwrap_or(1);
+        let loads = self.system.load_average();
+        let cores = self.system.physical_core_count().unwrap_or(1);
         Ok(LoadInfo { loads, cores })
     }
+
+    fn refresh_interval(&self) -> Option<Duration> {
+        Some(Duration::from_secs(2))
+    }
 }
 
 impl LoadSegmentRenderer {
-    fn format_loads(&self, info: &LoadInfo) -> Vec<Span> {
-        let warning_threshold = info.cores as f64 * 0.9;
-        let error_threshold = info.cores as f64 * 1.5;
+    fn style(&self, load: f64, theme: &Theme) -> Style {
+        let warning_threshold = self.info.cores as f64 * 0.9;
+        let error_threshold = self.info.cores as f64 * 1.5;
+        theme.status(load, warning_threshold, error_threshold)
+    }
 
+    fn format_loads(&self, info: &LoadInfo, theme: &Theme) -> Vec<Span<'_>> {
         let colored_loads: Vec<Span> = [info.loads.one, info.loads.five, info.loads.fifteen]
             .iter()
             .map(|&load| {
                 let content = format!("{:.2}", load);
-                if load < warning_threshold {
-                    Span::styled(content, Style::default().fg(Color::Green))
-                } else if load < error_threshold {
-                    Span::styled(content, Style::default().fg(Color::Yellow))
-                } else {
-                    Span::styled(content, Style::default().fg(Color::Red))
-                }
+                Span::styled(content, self.style(load, theme))
             })
             .collect();
 
         vec![
             colored_loads[0].clone(),
-            Span::raw(", "),
+            Span::raw(theme.separator),
             colored_loads[1].clone(),
-            Span::raw(", "),
+            Span::raw(theme.separator),
             colored_loads[2].clone(),
             Span::raw(format!(" (across {} cores)", info.cores)),
         ]
@@ -63,20 +73,29 @@ impl LoadSegmentRenderer {
 }
 
 impl SegmentRenderer<LoadInfo> for LoadSegmentRenderer {
-    fn height(&self) -> u16 {
+    fn height(&self, _width: u16, _theme: &Theme) -> u16 {
         1
     }
 
-    fn render(&self, frame: &mut Frame, area: Rect) -> Result<()> {
+    fn render(&self, frame: &mut Frame, area: Rect, theme: &Theme) -> Result<()> {
         let [label_area, data_area, _padding] = create_label_data_layout(area);
 
-        frame.render_widget(label("Load"), label_area);
+        frame.render_widget(label("Load", theme), label_area);
 
-        let formatted_loads = self.format_loads(&self.info);
+        let formatted_loads = self.format_loads(&self.info, theme);
         frame.render_widget(Paragraph::new(Line::from(formatted_loads)), data_area);
 
         Ok(())
     }
+
+    fn summary(&self, theme: &Theme) -> Vec<Line<'static>> {
+        let load = self.info.loads.one;
+        vec![summary_item(
+            "load",
+            format!("{:.2}", load),
+            self.style(load, theme),
+        )]
+    }
 }
 
 impl From<Box<LoadInfo>> for LoadSegmentRenderer {
@@ -84,3 +103,10 @@ impl From<Box<LoadInfo>> for LoadSegmentRenderer {
         Self { info: *info }
     }
 }
+
+pub fn register(registry: &mut Registry) {
+    registry.register("load", |options| {
+        let builder = options.parse::<LoadInfoBuilder>()?.system(options.system());
+        Ok(segment::boxed::<LoadInfo, LoadSegmentRenderer>(builder))
+    });
+}
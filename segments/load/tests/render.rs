@@ -0,0 +1,12 @@
+use load::{LoadInfo, LoadSegmentRenderer};
+use segment::testing::render;
+use segment::{assert_snapshot, fixture};
+
+#[test]
+fn renders_load_averages() {
+    let info: LoadInfo = fixture!("load");
+    assert_snapshot!(
+        "renders_load_averages",
+        render::<_, LoadSegmentRenderer>(info, 60)
+    );
+}
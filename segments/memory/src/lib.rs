@@ -1,61 +1,98 @@
 use anyhow::Result;
-use ratatui::{
-    prelude::*,
-    style::{Color, Style},
-    widgets::*,
-};
+use ratatui::{prelude::*, widgets::*};
 use segment::*;
-use sysinfo::System;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
 use tracing::instrument;
 #[derive(Debug)]
 pub struct MemorySegmentRenderer {
     info: MemoryInfo,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct MemoryInfo {
-    used_memory: f64,
-    available_memory: f64,
-    total_memory: f64,
+    used_bytes: u64,
+    available_bytes: u64,
+    total_bytes: u64,
     warning_threshold_percent: f64,
     critical_threshold_percent: f64,
 }
 
 impl MemoryInfo {
-    fn format_gb(&self, value: f64) -> String {
-        if value < 2.0 {
-            format!("{:.2} GB", value)
+    fn format_gb(&self, bytes: u64) -> String {
+        let gb = bytes as f64 / 1_073_741_824.0; // 1024^3
+        if gb < 2.0 {
+            format!("{:.2} GB", gb)
         } else {
-            format!("{} GB", value.round() as u64)
+            format!("{} GB", gb.round() as u64)
         }
     }
 
     fn used_memory_formatted(&self) -> String {
-        self.format_gb(self.used_memory)
+        self.format_gb(self.used_bytes)
     }
 
     fn available_memory_formatted(&self) -> String {
-        self.format_gb(self.available_memory)
+        self.format_gb(self.available_bytes)
     }
 
     fn total_memory_formatted(&self) -> String {
-        self.format_gb(self.total_memory)
+        self.format_gb(self.total_bytes)
+    }
+
+    fn percent_used(&self) -> f64 {
+        if self.total_bytes == 0 {
+            return 0.0;
+        }
+        self.used_bytes as f64 / self.total_bytes as f64 * 100.0
+    }
+}
+
+impl MemoryInfo {
+    fn severity(&self) -> Severity {
+        Severity::from_thresholds(
+            self.percent_used(),
+            self.warning_threshold_percent,
+            self.critical_threshold_percent,
+        )
     }
 }
 
-impl Info for MemoryInfo {}
+impl Info for MemoryInfo {
+    /// Only crossing a threshold is worth mentioning; memory use moves around all the time.
+    fn changes(&self, previous: &Self, _elapsed: Duration) -> Vec<Change> {
+        let severity = self.severity();
+        let direction = match severity.cmp(&previous.severity()) {
+            std::cmp::Ordering::Greater => "up",
+            std::cmp::Ordering::Less => "down",
+            std::cmp::Ordering::Equal => return Vec::new(),
+        };
+        vec![Change::new(
+            format!(
+                "memory use {direction} from {:.0}% to {:.0}%",
+                previous.percent_used(),
+                self.percent_used()
+            ),
+            severity,
+        )]
+    }
+}
 
-#[derive(Debug)]
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct MemoryInfoBuilder {
     warning_threshold_percent: f64,
     critical_threshold_percent: f64,
+    #[serde(skip)]
+    system: SharedSystem,
 }
 
 impl Default for MemoryInfoBuilder {
     fn default() -> Self {
         Self {
-            warning_threshold_percent: 20.0,
-            critical_threshold_percent: 10.0,
+            warning_threshold_percent: 80.0,
+            critical_threshold_percent: 90.0,
+            system: SharedSystem::default(),
         }
     }
 }
@@ -74,47 +111,49 @@ impl MemoryInfoBuilder {
         self.critical_threshold_percent = percent;
         self
     }
+
+    pub fn system(mut self, system: SharedSystem) -> Self {
+        self.system = system;
+        self
+    }
 }
 
 impl InfoBuilder<MemoryInfo> for MemoryInfoBuilder {
     #[instrument(skip(self), fields(builder_type = "MemoryInfoBuilder"))]
     async fn build(&self) -> Result<MemoryInfo> {
-        let mut sys = System::new_all();
-        sys.refresh_all();
-
-        let bytes_to_gb = |bytes: u64| -> f64 {
-            bytes as f64 / 1_073_741_824.0 // 1024^3
-        };
+        let memory = self.system.memory();
 
         Ok(MemoryInfo {
-            used_memory: bytes_to_gb(sys.used_memory()),
-            available_memory: bytes_to_gb(sys.available_memory()),
-            total_memory: bytes_to_gb(sys.total_memory()),
+            used_bytes: memory.used_bytes,
+            available_bytes: memory.available_bytes,
+            total_bytes: memory.total_bytes,
             warning_threshold_percent: self.warning_threshold_percent,
             critical_threshold_percent: self.critical_threshold_percent,
         })
     }
+
+    fn refresh_interval(&self) -> Option<Duration> {
+        Some(Duration::from_secs(2))
+    }
 }
 
 impl SegmentRenderer<MemoryInfo> for MemorySegmentRenderer {
-    fn height(&self) -> u16 {
+    fn height(&self, _width: u16, _theme: &Theme) -> u16 {
         2
     }
 
-    fn render(&self, frame: &mut Frame, area: Rect) -> Result<()> {
+    fn render(&self, frame: &mut Frame, area: Rect, theme: &Theme) -> Result<()> {
         let [label_area, data_area, _padding] = create_label_data_layout(area);
 
-        frame.render_widget(label("RAM"), label_area);
+        frame.render_widget(label("RAM", theme), label_area);
 
-        let used_percentage = (self.info.used_memory / self.info.total_memory) * 100.0;
+        let used_percentage = self.info.percent_used();
 
-        let usage_color = if used_percentage >= self.info.critical_threshold_percent {
-            Color::Red
-        } else if used_percentage >= self.info.warning_threshold_percent {
-            Color::Yellow
-        } else {
-            Color::Green
-        };
+        let usage_style = theme.status(
+            used_percentage,
+            self.info.warning_threshold_percent,
+            self.info.critical_threshold_percent,
+        );
 
         let summary = Line::from(vec![
             Span::raw(format!(
@@ -124,7 +163,7 @@ impl SegmentRenderer<MemoryInfo> for MemorySegmentRenderer {
             )),
             Span::styled(
                 format!("{} free", self.info.available_memory_formatted()),
-                Style::default().fg(usage_color),
+                usage_style,
             ),
             Span::raw(")"),
         ]);
@@ -132,15 +171,29 @@ impl SegmentRenderer<MemoryInfo> for MemorySegmentRenderer {
         frame.render_widget(
             LineGauge::default()
                 .block(Block::default().title(summary))
-                .filled_style(Style::default().fg(Color::Red).add_modifier(Modifier::BOLD))
-                .unfilled_style(Style::default().fg(Color::Green))
-                .line_set(symbols::line::THICK)
+                .filled_style(theme.gauge_filled)
+                .unfilled_style(theme.gauge_unfilled)
+                .line_set(theme.gauge_line)
                 .ratio(used_percentage / 100.0),
             data_area,
         );
 
         Ok(())
     }
+
+    fn summary(&self, theme: &Theme) -> Vec<Line<'static>> {
+        let used_percentage = self.info.percent_used();
+        let style = theme.status(
+            used_percentage,
+            self.info.warning_threshold_percent,
+            self.info.critical_threshold_percent,
+        );
+        vec![summary_item(
+            "mem",
+            format!("{:.0}%", used_percentage),
+            style,
+        )]
+    }
 }
 
 impl From<Box<MemoryInfo>> for MemorySegmentRenderer {
@@ -148,3 +201,12 @@ impl From<Box<MemoryInfo>> for MemorySegmentRenderer {
         Self { info: *info }
     }
 }
+
+pub fn register(registry: &mut Registry) {
+    registry.register("memory", |options| {
+        let builder = options
+            .parse::<MemoryInfoBuilder>()?
+            .system(options.system());
+        Ok(segment::boxed::<MemoryInfo, MemorySegmentRenderer>(builder))
+    });
+}
@@ -0,0 +1,21 @@
+use memory::{MemoryInfo, MemorySegmentRenderer};
+use segment::testing::{render, render_with_theme};
+use segment::{assert_snapshot, fixture, Theme};
+
+#[test]
+fn renders_usage_bar() {
+    let info: MemoryInfo = fixture!("memory");
+    assert_snapshot!(
+        "renders_usage_bar",
+        render::<_, MemorySegmentRenderer>(info, 60)
+    );
+}
+
+#[test]
+fn renders_usage_bar_monochrome() {
+    let info: MemoryInfo = fixture!("memory");
+    assert_snapshot!(
+        "renders_usage_bar_monochrome",
+        render_with_theme::<_, MemorySegmentRenderer>(info, 60, &Theme::monochrome())
+    );
+}
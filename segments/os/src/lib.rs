@@ -1,15 +1,17 @@
 use anyhow::Result;
 use ratatui::{prelude::*, widgets::*};
 use segment::*;
+use serde::{Deserialize, Serialize};
 use tracing::instrument;
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct OsInfo {
     os_string: String,
 }
 
 impl Info for OsInfo {}
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct OsInfoBuilder {}
 
 impl InfoBuilder<OsInfo> for OsInfoBuilder {
@@ -28,14 +30,14 @@ pub struct OsSegmentRenderer {
 }
 
 impl SegmentRenderer<OsInfo> for OsSegmentRenderer {
-    fn height(&self) -> u16 {
+    fn height(&self, _width: u16, _theme: &Theme) -> u16 {
         1
     }
 
-    fn render(&self, frame: &mut Frame, area: Rect) -> Result<()> {
+    fn render(&self, frame: &mut Frame, area: Rect, theme: &Theme) -> Result<()> {
         let [label_area, data_area, _padding] = create_label_data_layout(area);
 
-        frame.render_widget(label("OS"), label_area);
+        frame.render_widget(label("OS", theme), label_area);
 
         frame.render_widget(Paragraph::new(self.info.os_string.clone()), data_area);
 
@@ -48,3 +50,11 @@ impl From<Box<OsInfo>> for OsSegmentRenderer {
         Self { info: *info }
     }
 }
+
+pub fn register(registry: &mut Registry) {
+    registry.register("os", |options| {
+        Ok(segment::boxed::<OsInfo, OsSegmentRenderer>(
+            options.parse::<OsInfoBuilder>()?,
+        ))
+    });
+}
@@ -1,44 +1,137 @@
 use anyhow::Result;
-use fortune::{Fortunes, NoFortunesError};
+use fortune::{
+    Filter, Fortunes, Offensive, Options, SearchPath, Selection, Source, Sources, Weighting,
+};
 use ratatui::prelude::*;
 use ratatui::widgets::*;
 use segment::*;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 use tracing::instrument;
 
-fn choose_fortune() -> Result<String, NoFortunesError> {
-    // TODO: support multiple fortune files: pickleisms, collected-quotes
-    let fortune_path =
-        String::from("/opt/homebrew/opt/fortune/share/games/fortunes/collected-quotes");
-    let fortune_file = Fortunes::from_file(&fortune_path).unwrap();
-    let fortune = fortune_file.choose_one()?;
+const DEFAULT_FORTUNE: &str = "collected-quotes";
 
-    Ok(fortune.to_string())
-}
+/// State file remembering recent quotes, for `selection = "no-repeat"`.
+const HISTORY_FILE: &str = "quote-history.json";
 
-#[derive(Debug)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct QuoteInfo {
     quote: String,
 }
 
 impl Info for QuoteInfo {}
 
-impl Default for QuoteInfo {
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct QuoteInfoBuilder {
+    /// Fortune databases to quote from, by name (like `collected-quotes`) or path, each
+    /// optionally with a percentage, like `30% pickleisms`.
+    fortune: Sources,
+    /// How to choose between databases without a percentage.
+    weighting: Weighting,
+    /// Directories to look for the databases in before `$FORTUNE_PATH` and the usual places.
+    fortune_path: Vec<PathBuf>,
+    /// Whether to quote from the offensive databases too, or only from them.
+    offensive: Offensive,
+    /// Which fortunes to choose from, by length and pattern.
+    #[serde(flatten)]
+    filter: Filter,
+    /// A new quote at random every time, the same one all day, or none of the recent ones.
+    selection: Selection,
+    #[serde(skip)]
+    seed: Option<u64>,
+    #[serde(skip)]
+    history: Option<PathBuf>,
+}
+
+impl Default for QuoteInfoBuilder {
     fn default() -> Self {
         Self {
-            quote: choose_fortune().unwrap(),
+            fortune: Sources(vec![Source {
+                name: DEFAULT_FORTUNE.to_string(),
+                percent: None,
+            }]),
+            weighting: Weighting::default(),
+            fortune_path: Vec::new(),
+            offensive: Offensive::default(),
+            filter: Filter::default(),
+            selection: Selection::default(),
+            seed: None,
+            history: None,
         }
     }
 }
 
-#[derive(Debug, Default)]
-pub struct QuoteInfoBuilder;
-
 impl QuoteInfoBuilder {
+    pub fn fortune(mut self, fortune: Vec<Source>) -> Self {
+        self.fortune = Sources(fortune);
+        self
+    }
+
+    pub fn weighting(mut self, weighting: Weighting) -> Self {
+        self.weighting = weighting;
+        self
+    }
+
+    pub fn fortune_path(mut self, fortune_path: Vec<PathBuf>) -> Self {
+        self.fortune_path = fortune_path;
+        self
+    }
+
+    pub fn offensive(mut self, offensive: Offensive) -> Self {
+        self.offensive = offensive;
+        self
+    }
+
+    pub fn filter(mut self, filter: Filter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    pub fn selection(mut self, selection: Selection) -> Self {
+        self.selection = selection;
+        self
+    }
+
+    pub fn seed(mut self, seed: Option<u64>) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    pub fn history(mut self, history: Option<PathBuf>) -> Self {
+        self.history = history;
+        self
+    }
+
+    fn options(&self) -> Options {
+        Options {
+            weighting: self.weighting,
+            offensive: self.offensive,
+            filter: self.filter.clone(),
+        }
+    }
+
+    fn search_path(&self) -> SearchPath {
+        SearchPath::from_env().with_dirs_first(self.fortune_path.iter().cloned())
+    }
+}
+
+impl InfoBuilder<QuoteInfo> for QuoteInfoBuilder {
     #[instrument(skip(self), fields(builder_type = "QuoteInfoBuilder"))]
-    pub async fn build(&self) -> Result<QuoteInfo> {
-        let quote = choose_fortune()?;
+    async fn build(&self) -> Result<QuoteInfo> {
+        let fortunes = Fortunes::load(&self.fortune.0, &self.search_path(), &self.options())?;
+        let quote = fortunes.choose(self.selection, self.seed, self.history.as_deref())?;
         Ok(QuoteInfo { quote })
     }
+
+    fn preflight(&self) -> Vec<Check> {
+        let search_path = self.search_path();
+        self.fortune
+            .0
+            .iter()
+            .flat_map(|source| search_path.checks(&source.name, self.offensive, "quote"))
+            .collect()
+    }
 }
 
 #[derive(Debug, Default)]
@@ -46,26 +139,38 @@ pub struct QuoteSegmentRenderer {
     info: QuoteInfo,
 }
 
-impl SegmentRenderer<QuoteInfo> for QuoteSegmentRenderer {
-    fn height(&self) -> u16 {
-        // Add 2 to account for the new padding lines
-        self.info.quote.lines().count() as u16 + 2
-    }
+/// Blank columns on either side of the quote.
+const HORIZONTAL_PADDING: u16 = 4;
 
-    fn render(&self, frame: &mut Frame, area: Rect) -> Result<()> {
+impl QuoteSegmentRenderer {
+    fn paragraph(&self) -> Paragraph<'_> {
         let mut styled_lines = Vec::with_capacity(self.info.quote.lines().count() + 2);
         styled_lines.push(Line::default()); // Add an empty line for top padding
         styled_lines.extend(self.info.quote.lines().map(Line::from));
         styled_lines.push(Line::default()); // Add an empty line for bottom padding
 
+        Paragraph::new(styled_lines).wrap(Wrap { trim: true })
+    }
+}
+
+impl SegmentRenderer<QuoteInfo> for QuoteSegmentRenderer {
+    fn height(&self, width: u16, _theme: &Theme) -> u16 {
+        paragraph_height(
+            &self.paragraph(),
+            width.saturating_sub(HORIZONTAL_PADDING * 2),
+        )
+    }
+
+    fn full_width(&self) -> bool {
+        true
+    }
+
+    fn render(&self, frame: &mut Frame, area: Rect, theme: &Theme) -> Result<()> {
         let block = Block::default()
             .borders(Borders::NONE)
-            .padding(Padding::horizontal(4));
+            .padding(Padding::horizontal(HORIZONTAL_PADDING));
 
-        let paragraph = Paragraph::new(styled_lines)
-            .wrap(Wrap { trim: true })
-            .style(Style::default().dim())
-            .block(block);
+        let paragraph = self.paragraph().style(theme.dim).block(block);
 
         frame.render_widget(paragraph, area);
 
@@ -78,3 +183,13 @@ impl From<Box<QuoteInfo>> for QuoteSegmentRenderer {
         Self { info: *info }
     }
 }
+
+pub fn register(registry: &mut Registry) {
+    registry.register("quote", |options| {
+        let builder = options
+            .parse::<QuoteInfoBuilder>()?
+            .seed(options.seed())
+            .history(options.state_dir().map(|dir| dir.join(HISTORY_FILE)));
+        Ok(segment::boxed::<QuoteInfo, QuoteSegmentRenderer>(builder))
+    });
+}
@@ -1,13 +1,13 @@
 use anyhow::Result;
 use ratatui::{
     prelude::*,
-    style::{Color, Style},
     text::{Line, Span},
     widgets::Paragraph,
 };
-use sysinfo::{ComponentExt, System, SystemExt};
 
 use segment::*;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
 
 #[derive(Debug)]
 pub enum TemperatureStatus {
@@ -16,7 +16,7 @@ pub enum TemperatureStatus {
     Critical,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct SensorTemperature {
     name: String,
     temperature: f32,
@@ -34,9 +34,17 @@ impl SensorTemperature {
             TemperatureStatus::Ok
         }
     }
+
+    fn style(&self, theme: &Theme) -> Style {
+        match self.status() {
+            TemperatureStatus::Critical => theme.critical,
+            TemperatureStatus::High => theme.warn,
+            TemperatureStatus::Ok => theme.ok,
+        }
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct TemperaturesInfo {
     sensors: Vec<SensorTemperature>,
 }
@@ -48,56 +56,83 @@ pub struct TemperaturesSegmentRenderer {
     info: Box<TemperaturesInfo>,
 }
 
+impl TemperaturesSegmentRenderer {
+    /// The sensors, wrapped onto as many lines as they need in `width` columns.
+    fn lines(&self, width: u16, theme: &Theme) -> Vec<Line<'_>> {
+        let sensors = self
+            .info
+            .sensors
+            .iter()
+            .map(|sensor| {
+                vec![Span::styled(
+                    format!("{} {:.1}°C", sensor.name, sensor.temperature),
+                    sensor.style(theme),
+                )]
+            })
+            .collect();
+
+        flow(sensors, theme.separator, width)
+    }
+}
+
 impl SegmentRenderer<TemperaturesInfo> for TemperaturesSegmentRenderer {
-    fn height(&self) -> u16 {
-        1
+    fn height(&self, width: u16, theme: &Theme) -> u16 {
+        self.lines(data_width(width), theme).len().max(1) as u16
     }
 
-    fn render(&self, frame: &mut Frame, area: Rect) -> Result<()> {
+    fn render(&self, frame: &mut Frame, area: Rect, theme: &Theme) -> Result<()> {
         let [label_area, data_area, _padding] = create_label_data_layout(area);
 
-        frame.render_widget(label("Temps"), label_area);
-
-        let mut spans = Vec::new();
-
-        for (index, sensor) in self.info.sensors.iter().enumerate() {
-            let color = match sensor.status() {
-                TemperatureStatus::Critical => Color::Red,
-                TemperatureStatus::High => Color::Yellow,
-                TemperatureStatus::Ok => Color::Green,
-            };
-
-            spans.push(Span::styled(
-                format!("{} {:.1}°C", sensor.name, sensor.temperature),
-                Style::default().fg(color),
-            ));
-
-            if index < self.info.sensors.len() - 1 {
-                spans.push(Span::raw(", "));
-            }
-        }
-
-        let temps_line = Line::from(spans);
-        let temps_paragraph = Paragraph::new(temps_line);
-        frame.render_widget(temps_paragraph, data_area);
+        frame.render_widget(label("Temps", theme), label_area);
+        frame.render_widget(
+            Paragraph::new(self.lines(data_area.width, theme)),
+            data_area,
+        );
 
         Ok(())
     }
+
+    fn summary(&self, theme: &Theme) -> Vec<Line<'static>> {
+        // The hottest sensor is the one worth a glance
+        self.info
+            .sensors
+            .iter()
+            .max_by(|a, b| a.temperature.total_cmp(&b.temperature))
+            .map(|sensor| {
+                summary_item(
+                    "temp",
+                    format!("{:.0}°C", sensor.temperature),
+                    sensor.style(theme),
+                )
+            })
+            .into_iter()
+            .collect()
+    }
 }
 
 impl From<Box<TemperaturesInfo>> for TemperaturesSegmentRenderer {
     fn from(info: Box<TemperaturesInfo>) -> Self {
-        Self { info: info }
+        Self { info }
     }
 }
 
-#[derive(Debug, Default)]
-pub struct TemperaturesInfoBuilder;
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TemperaturesInfoBuilder {
+    #[serde(skip)]
+    system: SharedSystem,
+}
+
+impl TemperaturesInfoBuilder {
+    pub fn system(mut self, system: SharedSystem) -> Self {
+        self.system = system;
+        self
+    }
+}
 
 impl InfoBuilder<TemperaturesInfo> for TemperaturesInfoBuilder {
     async fn build(&self) -> Result<TemperaturesInfo> {
-        let mut sys = System::new_all();
-        sys.refresh_components();
+        let components = self.system.components();
 
         let mut cpu_temp = 0.0;
         let mut cpu_count = 0;
@@ -114,30 +149,30 @@ impl InfoBuilder<TemperaturesInfo> for TemperaturesInfoBuilder {
         let mut battery_high = 0.0;
         let mut battery_critical = 0.0;
 
-        for component in sys.components() {
-            let name = component.label().to_lowercase();
-            let temp = component.temperature();
+        for component in &components {
+            let name = component.label.to_lowercase();
+            let temp = component.temperature;
 
             if name.contains("cpu") || name.contains("tdie") {
                 cpu_temp += temp;
                 cpu_count += 1;
                 if cpu_count == 1 {
-                    cpu_high = component.max();
-                    cpu_critical = component.critical().unwrap_or(100.0);
+                    cpu_high = component.max;
+                    cpu_critical = component.critical.unwrap_or(100.0);
                 }
             } else if name.contains("gpu") {
                 gpu_temp += temp;
                 gpu_count += 1;
                 if gpu_count == 1 {
-                    gpu_high = component.max();
-                    gpu_critical = component.critical().unwrap_or(100.0);
+                    gpu_high = component.max;
+                    gpu_critical = component.critical.unwrap_or(100.0);
                 }
             } else if name.contains("battery") {
                 battery_temp += temp;
                 battery_count += 1;
                 if battery_count == 1 {
-                    battery_high = component.max();
-                    battery_critical = component.critical().unwrap_or(60.0);
+                    battery_high = component.max;
+                    battery_critical = component.critical.unwrap_or(60.0);
                 }
             }
         }
@@ -182,4 +217,32 @@ impl InfoBuilder<TemperaturesInfo> for TemperaturesInfoBuilder {
                 .collect(),
         })
     }
+
+    fn preflight(&self) -> Vec<Check> {
+        if self.system.components().is_empty() {
+            vec![
+                Check::new("temperature sensors", CheckStatus::Unsupported).with_hint(
+                    "No sensors were reported; on Linux, load the hwmon drivers (e.g. with `sensors-detect`)",
+                ),
+            ]
+        } else {
+            vec![Check::ok("temperature sensors")]
+        }
+    }
+
+    fn refresh_interval(&self) -> Option<Duration> {
+        Some(Duration::from_secs(5))
+    }
+}
+
+pub fn register(registry: &mut Registry) {
+    registry.register("temperatures", |options| {
+        let builder = options
+            .parse::<TemperaturesInfoBuilder>()?
+            .system(options.system());
+        Ok(segment::boxed::<
+            TemperaturesInfo,
+            TemperaturesSegmentRenderer,
+        >(builder))
+    });
 }
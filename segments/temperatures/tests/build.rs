@@ -0,0 +1,20 @@
+use segment::testing::render;
+use segment::{assert_snapshot, InfoBuilder, SharedSystem, SystemSnapshot};
+use std::path::Path;
+use temperatures::{TemperaturesInfoBuilder, TemperaturesSegmentRenderer};
+
+#[tokio::test]
+async fn averages_sensors_by_kind() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/system.json");
+    let system = SharedSystem::new(SystemSnapshot::load(&path).unwrap());
+    let info = TemperaturesInfoBuilder::default()
+        .system(system)
+        .build()
+        .await
+        .unwrap();
+
+    assert_snapshot!(
+        "averages_sensors_by_kind",
+        render::<_, TemperaturesSegmentRenderer>(info, 80)
+    );
+}
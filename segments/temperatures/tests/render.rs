@@ -0,0 +1,12 @@
+use segment::testing::render;
+use segment::{assert_snapshot, fixture};
+use temperatures::{TemperaturesInfo, TemperaturesSegmentRenderer};
+
+#[test]
+fn colors_by_threshold() {
+    let info: TemperaturesInfo = fixture!("sensors");
+    assert_snapshot!(
+        "colors_by_threshold",
+        render::<_, TemperaturesSegmentRenderer>(info, 80)
+    );
+}
@@ -4,23 +4,41 @@ use plist::Value;
 use ratatui::{prelude::*, widgets::*};
 use segment::*;
 use semver::Version;
+use serde::{Deserialize, Serialize};
 use std::fs::File;
 use std::io::BufReader;
+use std::time::Duration;
 use tracing::{debug, error, instrument};
 
+const PRODUCT_METADATA: &str = "/Library/Updates/ProductMetadata.plist";
+
 #[derive(Default, Debug)]
 pub struct UpdatesSegmentRenderer {
     info: UpdatesInfo,
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct UpdatesInfo {
     updates: Vec<String>,
 }
 
-impl Info for UpdatesInfo {}
+impl Info for UpdatesInfo {
+    fn changes(&self, previous: &Self, _elapsed: Duration) -> Vec<Change> {
+        let new = self
+            .updates
+            .iter()
+            .filter(|update| !previous.updates.contains(update))
+            .count();
+        match new {
+            0 => Vec::new(),
+            1 => vec![Change::notice("1 new update")],
+            count => vec![Change::notice(format!("{count} new updates"))],
+        }
+    }
+}
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct UpdatesInfoBuilder {}
 
 impl InfoBuilder<UpdatesInfo> for UpdatesInfoBuilder {
@@ -31,8 +49,7 @@ impl InfoBuilder<UpdatesInfo> for UpdatesInfoBuilder {
             .context("Failed to parse current OS version")?;
         debug!("Current OS version: {}", current_version);
 
-        let file = File::open("/Library/Updates/ProductMetadata.plist")
-            .context("Failed to open ProductMetadata.plist")?;
+        let file = File::open(PRODUCT_METADATA).context("Failed to open ProductMetadata.plist")?;
         debug!("Successfully opened ProductMetadata.plist");
 
         let reader = BufReader::new(file);
@@ -99,34 +116,59 @@ impl InfoBuilder<UpdatesInfo> for UpdatesInfoBuilder {
         debug!("Found {} updates", updates.len());
         Ok(UpdatesInfo { updates })
     }
-}
 
-impl SegmentRenderer<UpdatesInfo> for UpdatesSegmentRenderer {
-    fn height(&self) -> u16 {
-        (self.info.updates.len()).max(1) as u16
+    fn preflight(&self) -> Vec<Check> {
+        if !cfg!(target_os = "macos") {
+            return vec![
+                Check::new(PRODUCT_METADATA, CheckStatus::Unsupported).with_hint(
+                    "Only macOS software updates are supported; remove `updates` from `segments`",
+                ),
+            ];
+        }
+
+        vec![Check::readable(PRODUCT_METADATA)
+            .with_hint("Run `softwareupdate --list` once so macOS writes it")]
     }
 
-    fn render(&self, frame: &mut Frame, area: Rect) -> Result<()> {
-        let [label_area, data_area, _padding] = create_label_data_layout(area);
-
-        frame.render_widget(label("Updates"), label_area);
-
-        let updates_text = if self.info.updates.is_empty() {
-            "No updates available".to_string()
-        } else {
-            self.info.updates.join("\n")
-        };
+    fn refresh_interval(&self) -> Option<Duration> {
+        Some(Duration::from_secs(60 * 60))
+    }
+}
 
+impl UpdatesSegmentRenderer {
+    fn paragraph(&self, theme: &Theme) -> Paragraph<'_> {
         let paragraph = if self.info.updates.is_empty() {
-            Paragraph::new(updates_text).style(Style::default().dim())
+            Paragraph::new("No updates available").style(theme.dim)
         } else {
-            Paragraph::new(updates_text)
+            Paragraph::new(self.info.updates.join("\n"))
         };
+        paragraph.wrap(Wrap { trim: true })
+    }
+}
+
+impl SegmentRenderer<UpdatesInfo> for UpdatesSegmentRenderer {
+    fn height(&self, width: u16, theme: &Theme) -> u16 {
+        paragraph_height(&self.paragraph(theme), data_width(width)).max(1)
+    }
 
-        frame.render_widget(paragraph, data_area);
+    fn render(&self, frame: &mut Frame, area: Rect, theme: &Theme) -> Result<()> {
+        let [label_area, data_area, _padding] = create_label_data_layout(area);
+
+        frame.render_widget(label("Updates", theme), label_area);
+        frame.render_widget(self.paragraph(theme), data_area);
 
         Ok(())
     }
+
+    fn summary(&self, theme: &Theme) -> Vec<Line<'static>> {
+        match self.info.updates.len() {
+            0 => Vec::new(),
+            count => vec![Line::styled(
+                format!("{count} update{}", if count == 1 { "" } else { "s" }),
+                theme.warn,
+            )],
+        }
+    }
 }
 
 impl From<Box<UpdatesInfo>> for UpdatesSegmentRenderer {
@@ -134,3 +176,11 @@ impl From<Box<UpdatesInfo>> for UpdatesSegmentRenderer {
         Self { info: *info }
     }
 }
+
+pub fn register(registry: &mut Registry) {
+    registry.register("updates", |options| {
+        Ok(segment::boxed::<UpdatesInfo, UpdatesSegmentRenderer>(
+            options.parse::<UpdatesInfoBuilder>()?,
+        ))
+    });
+}
@@ -1,30 +1,22 @@
 use anyhow::Result;
 use ratatui::{prelude::*, widgets::*};
 use segment::*;
-use sysinfo::System;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
 use tracing::instrument;
 #[derive(Debug)]
 pub struct UptimeSegmentRenderer {
     info: UptimeInfo,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct UptimeInfo {
-    uptime: String,
+    seconds: u64,
 }
 
-impl Info for UptimeInfo {}
-
-#[derive(Debug, Default)]
-pub struct UptimeInfoBuilder {}
-
-impl InfoBuilder<UptimeInfo> for UptimeInfoBuilder {
-    #[instrument(skip(self), fields(builder_type = "UptimeInfoBuilder"))]
-    async fn build(&self) -> Result<UptimeInfo> {
-        let mut sys = System::new_all();
-        sys.refresh_all();
-
-        let uptime_secs = System::uptime();
+impl UptimeInfo {
+    fn formatted(&self) -> String {
+        let uptime_secs = self.seconds;
         let days = uptime_secs / 86400;
         let hours = (uptime_secs % 86400) / 3600;
         let minutes = (uptime_secs % 3600) / 60;
@@ -56,34 +48,96 @@ impl InfoBuilder<UptimeInfo> for UptimeInfoBuilder {
             ));
         }
 
-        let uptime = uptime_parts.join(", ");
-        Ok(UptimeInfo { uptime })
+        uptime_parts.join(", ")
+    }
+
+    /// The two largest units, like "3d 4h" or "12m".
+    fn compact(&self) -> String {
+        let units = [
+            (self.seconds / 86400, "d"),
+            (self.seconds % 86400 / 3600, "h"),
+            (self.seconds % 3600 / 60, "m"),
+        ];
+        let parts: Vec<String> = units
+            .iter()
+            .skip_while(|(value, _)| *value == 0)
+            .take(2)
+            .filter(|(value, _)| *value > 0)
+            .map(|(value, unit)| format!("{value}{unit}"))
+            .collect();
+
+        if parts.is_empty() {
+            format!("{}s", self.seconds)
+        } else {
+            parts.join(" ")
+        }
+    }
+}
+
+impl Info for UptimeInfo {
+    fn changes(&self, previous: &Self, elapsed: Duration) -> Vec<Change> {
+        // Booted after it was last shown, or at least less up than it was then
+        if self.seconds < elapsed.as_secs() || self.seconds < previous.seconds {
+            vec![Change::warning("rebooted since last login")]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UptimeInfoBuilder {
+    #[serde(skip)]
+    system: SharedSystem,
+}
+
+impl UptimeInfoBuilder {
+    pub fn system(mut self, system: SharedSystem) -> Self {
+        self.system = system;
+        self
+    }
+}
+
+impl InfoBuilder<UptimeInfo> for UptimeInfoBuilder {
+    #[instrument(skip(self), fields(builder_type = "UptimeInfoBuilder"))]
+    async fn build(&self) -> Result<UptimeInfo> {
+        let seconds = self.system.uptime();
+        Ok(UptimeInfo { seconds })
+    }
+
+    fn refresh_interval(&self) -> Option<Duration> {
+        Some(Duration::from_secs(1))
     }
 }
 
 impl SegmentRenderer<UptimeInfo> for UptimeSegmentRenderer {
-    fn height(&self) -> u16 {
+    fn height(&self, _width: u16, _theme: &Theme) -> u16 {
         1
     }
 
-    fn render(&self, frame: &mut Frame, area: Rect) -> Result<()> {
+    fn render(&self, frame: &mut Frame, area: Rect, theme: &Theme) -> Result<()> {
         let [label_area, data_area, _padding] = create_label_data_layout(area);
 
-        frame.render_widget(label("Uptime"), label_area);
+        frame.render_widget(label("Uptime", theme), label_area);
 
-        let uptime_color = if self.info.uptime.contains("day") || self.info.uptime.contains("days")
-        {
-            Color::Yellow
+        // Only call out long uptimes, which usually mean updates are waiting on a reboot
+        let uptime_style = if self.info.seconds >= 86400 {
+            theme.warn
         } else {
-            Color::Reset
+            Style::default()
         };
         frame.render_widget(
-            Paragraph::new(self.info.uptime.clone()).fg(uptime_color),
+            Paragraph::new(self.info.formatted()).style(uptime_style),
             data_area,
         );
 
         Ok(())
     }
+
+    fn summary(&self, _theme: &Theme) -> Vec<Line<'static>> {
+        vec![Line::from(format!("up {}", self.info.compact()))]
+    }
 }
 
 impl From<Box<UptimeInfo>> for UptimeSegmentRenderer {
@@ -91,3 +145,12 @@ impl From<Box<UptimeInfo>> for UptimeSegmentRenderer {
         Self { info: *info }
     }
 }
+
+pub fn register(registry: &mut Registry) {
+    registry.register("uptime", |options| {
+        let builder = options
+            .parse::<UptimeInfoBuilder>()?
+            .system(options.system());
+        Ok(segment::boxed::<UptimeInfo, UptimeSegmentRenderer>(builder))
+    });
+}
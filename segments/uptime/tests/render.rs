@@ -0,0 +1,12 @@
+use segment::testing::render;
+use segment::{assert_snapshot, fixture};
+use uptime::{UptimeInfo, UptimeSegmentRenderer};
+
+#[test]
+fn renders_duration() {
+    let info: UptimeInfo = fixture!("uptime");
+    assert_snapshot!(
+        "renders_duration",
+        render::<_, UptimeSegmentRenderer>(info, 60)
+    );
+}
@@ -1,6 +1,7 @@
 use anyhow::Result;
 use ratatui::{prelude::*, widgets::*};
 use segment::*;
+use serde::{Deserialize, Serialize};
 use tracing::instrument;
 use users::{get_current_uid, get_user_by_uid};
 
@@ -9,7 +10,7 @@ pub struct UserSegmentRenderer {
     info: UserInfo,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct UserInfo {
     username: String,
     hostname: String,
@@ -23,7 +24,8 @@ impl UserInfo {
 
 impl Info for UserInfo {}
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct UserInfoBuilder {}
 
 impl InfoBuilder<UserInfo> for UserInfoBuilder {
@@ -43,17 +45,17 @@ impl InfoBuilder<UserInfo> for UserInfoBuilder {
 }
 
 impl SegmentRenderer<UserInfo> for UserSegmentRenderer {
-    fn height(&self) -> u16 {
+    fn height(&self, _width: u16, _theme: &Theme) -> u16 {
         1
     }
 
-    fn render(&self, frame: &mut Frame, area: Rect) -> Result<()> {
+    fn render(&self, frame: &mut Frame, area: Rect, theme: &Theme) -> Result<()> {
         let [label_area, data_area, _padding] = create_label_data_layout(area);
 
-        frame.render_widget(label("User"), label_area);
+        frame.render_widget(label("User", theme), label_area);
 
         frame.render_widget(
-            Paragraph::new(self.info.user_with_hostname()).style(Style::default().dim()),
+            Paragraph::new(self.info.user_with_hostname()).style(theme.dim),
             data_area,
         );
 
@@ -66,3 +68,11 @@ impl From<Box<UserInfo>> for UserSegmentRenderer {
         Self { info: *info }
     }
 }
+
+pub fn register(registry: &mut Registry) {
+    registry.register("user", |options| {
+        Ok(segment::boxed::<UserInfo, UserSegmentRenderer>(
+            options.parse::<UserInfoBuilder>()?,
+        ))
+    });
+}
@@ -0,0 +1,403 @@
+use anyhow::{anyhow, Result};
+use humantime::format_duration;
+use segment::{Segment, SegmentBuilder, SegmentInfo, UnavailableSegment};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::runtime::Handle;
+use tokio::task::JoinHandle;
+use tokio::time::{timeout_at, Instant};
+use tracing::{debug, error, field, info_span, instrument, warn, Span};
+
+use crate::cache::Cache;
+use crate::config::{CachePolicy, CacheRefresh, ConfiguredSegment, OnError};
+
+tokio::task_local! {
+    /// Name of the segment whose builder is running on this task, for the panic hook.
+    static SEGMENT: String;
+}
+
+/// Report panics in segment builders through tracing instead of stderr, so a broken segment
+/// doesn't scribble over the MOTD. The panic still surfaces as that segment's error.
+pub fn install_panic_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        if SEGMENT
+            .try_with(|name| error!(segment = name.as_str(), "builder panicked: {info}"))
+            .is_err()
+        {
+            default_hook(info);
+        }
+    }));
+}
+
+/// The outcome of building every configured segment.
+#[derive(Debug)]
+pub struct Built {
+    pub segments: Vec<BuiltSegment>,
+    /// Segments shown from an expired cache entry, to be built again in the background.
+    pub stale: Vec<String>,
+}
+
+impl Built {
+    /// Renderers for the segments to show, after applying each segment's `on_error` policy.
+    pub fn into_renderers(self) -> Vec<Box<dyn Segment>> {
+        self.segments
+            .into_iter()
+            .filter_map(BuiltSegment::into_renderer)
+            .collect()
+    }
+}
+
+#[derive(Debug)]
+pub struct BuiltSegment {
+    pub name: String,
+    /// The segment's info, or why it couldn't be built.
+    pub info: Result<Box<dyn SegmentInfo>, String>,
+    pub on_error: OnError,
+    /// Whether `info` came from the cache rather than being built just now.
+    pub cached: bool,
+}
+
+impl BuiltSegment {
+    pub fn into_renderer(self) -> Option<Box<dyn Segment>> {
+        match self.info {
+            Ok(info) => Some(info.into_segment()),
+            Err(reason) => match self.on_error {
+                OnError::Show => Some(Box::new(UnavailableSegment::new(&self.name, reason))),
+                OnError::Hide | OnError::Fail => None,
+            },
+        }
+    }
+}
+
+pub type BuildTask = JoinHandle<Result<Box<dyn SegmentInfo>>>;
+
+/// Start building a segment on the blocking pool, inside `span`.
+pub fn spawn_build(name: String, builder: Arc<dyn SegmentBuilder>, span: Span) -> BuildTask {
+    let handle = Handle::current();
+    tokio::task::spawn_blocking(move || {
+        let _enter = span.enter();
+        handle.block_on(SEGMENT.scope(name, builder.build()))
+    })
+}
+
+enum Pending {
+    Cached(Box<dyn SegmentInfo>),
+    Building(BuildTask),
+}
+
+/// Build every segment concurrently. Failures abort the build only for segments whose
+/// `on_error` policy is `fail`.
+///
+/// Builders run on the blocking pool since most of them do synchronous work (sysinfo, files,
+/// processes); that keeps the runtime free to enforce each segment's `timeout` and the overall
+/// `budget`, after which the segment is given up on and treated as failed.
+///
+/// Segments with a `cache_ttl` are served from `cache` while their entry is fresh, and stored
+/// there whenever they are built.
+#[instrument(skip(segments, cache))]
+pub async fn build_segments(
+    segments: Vec<ConfiguredSegment>,
+    budget: Option<Duration>,
+    cache: Option<&Cache>,
+) -> Result<Built> {
+    let started = Instant::now();
+    let mut stale = Vec::new();
+
+    // Create tasks for building segment info, unless it's cached
+    let tasks: Vec<_> = segments
+        .into_iter()
+        .map(|segment| {
+            let span = info_span!("segment", name = segment.name, timed_out = field::Empty);
+            let cached = cached_info(&segment, cache, &mut stale);
+            let pending = match cached {
+                Some(info) => Pending::Cached(info),
+                None => Pending::Building(spawn_build(
+                    segment.name.clone(),
+                    segment.builder,
+                    span.clone(),
+                )),
+            };
+            (
+                segment.name,
+                segment.on_error,
+                segment.timeout,
+                segment.cache,
+                span,
+                pending,
+            )
+        })
+        .collect();
+
+    // Wait for each task in turn, keeping the configured order
+    let mut built = Vec::with_capacity(tasks.len());
+    for (name, on_error, timeout, policy, span, pending) in tasks {
+        let cached = matches!(pending, Pending::Cached(_));
+        let result = match pending {
+            Pending::Cached(info) => Ok(info),
+            Pending::Building(task) => {
+                let deadline = [
+                    timeout.map(|timeout| (started + timeout, timeout, "its")),
+                    budget.map(|budget| (started + budget, budget, "the overall")),
+                ]
+                .into_iter()
+                .flatten()
+                .min_by_key(|(deadline, _, _)| *deadline);
+
+                let result = wait_for(task, deadline, &span).await;
+                if let (Ok(info), Some(policy), Some(cache)) = (&result, &policy, cache) {
+                    store(cache, &name, policy, info.as_ref());
+                }
+                result
+            }
+        };
+
+        let info = match result {
+            Ok(info) => Ok(info),
+            Err(error) if on_error == OnError::Fail => {
+                return Err(error.context(format!("Segment `{name}` failed")))
+            }
+            Err(error) => {
+                warn!(segment = name.as_str(), "failed to build: {error:#}");
+                Err(format!("{error:#}"))
+            }
+        };
+
+        built.push(BuiltSegment {
+            name,
+            info,
+            on_error,
+            cached,
+        });
+    }
+
+    Ok(Built {
+        segments: built,
+        stale,
+    })
+}
+
+/// Build `segments` and store them in `cache`, however fresh their entries are. Like
+/// [`build_segments`], builders run on the blocking pool and are given up on after their
+/// `timeout`, so a stuck one can't keep the refresh running forever.
+#[instrument(skip(segments, cache))]
+pub async fn refresh_cache(segments: Vec<ConfiguredSegment>, cache: &Cache) {
+    let started = Instant::now();
+    let tasks: Vec<_> = segments
+        .into_iter()
+        .filter_map(|segment| {
+            let policy = segment.cache?;
+            let span = info_span!("segment", name = segment.name, timed_out = field::Empty);
+            let task = spawn_build(segment.name.clone(), segment.builder, span.clone());
+            let deadline = segment
+                .timeout
+                .map(|timeout| (started + timeout, timeout, "its"));
+            Some((segment.name, policy, span, deadline, task))
+        })
+        .collect();
+
+    for (name, policy, span, deadline, task) in tasks {
+        match wait_for(task, deadline, &span).await {
+            Ok(info) => store(cache, &name, &policy, info.as_ref()),
+            Err(error) => warn!(segment = name.as_str(), "failed to refresh: {error:#}"),
+        }
+    }
+}
+
+/// Wait for `task`, giving up on it once `deadline` passes.
+async fn wait_for(
+    mut task: BuildTask,
+    deadline: Option<(Instant, Duration, &str)>,
+    span: &Span,
+) -> Result<Box<dyn SegmentInfo>> {
+    let Some((deadline, limit, whose)) = deadline else {
+        return task.await?;
+    };
+
+    match timeout_at(deadline, &mut task).await {
+        Ok(result) => result?,
+        Err(_) => {
+            task.abort();
+            span.record("timed_out", true);
+            warn!(
+                parent: span,
+                budget_ms = limit.as_millis() as u64,
+                "exceeded {whose} time budget"
+            );
+            Err(anyhow!("timed out after {}", format_duration(limit)))
+        }
+    }
+}
+
+/// The segment's cached info, if it is fresh enough to show. Entries that have expired are
+/// still shown when the segment refreshes in the background, and `name` is added to `stale`.
+fn cached_info(
+    segment: &ConfiguredSegment,
+    cache: Option<&Cache>,
+    stale: &mut Vec<String>,
+) -> Option<Box<dyn SegmentInfo>> {
+    let policy = segment.cache.as_ref()?;
+    let cached = cache?.load(&segment.name, &policy.fingerprint)?;
+    let expired = cached.age >= policy.ttl;
+    if expired && policy.refresh == CacheRefresh::Inline {
+        return None;
+    }
+
+    let info = restore(segment.builder.as_ref(), &segment.name, cached.info)?;
+    debug!(segment = segment.name.as_str(), age = ?cached.age, expired, "using cached info");
+    if expired {
+        stale.push(segment.name.clone());
+    }
+    Some(info)
+}
+
+fn restore(
+    builder: &dyn SegmentBuilder,
+    name: &str,
+    value: serde_json::Value,
+) -> Option<Box<dyn SegmentInfo>> {
+    builder
+        .restore(value)
+        .inspect_err(|error| debug!(segment = name, "ignoring cached info: {error:#}"))
+        .ok()
+}
+
+fn store(cache: &Cache, name: &str, policy: &CachePolicy, info: &dyn SegmentInfo) {
+    if let Err(error) = info
+        .to_json()
+        .and_then(|value| cache.store(name, &policy.fingerprint, value))
+    {
+        warn!(segment = name, "failed to cache: {error:#}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use changes::{ChangesInfo, ChangesSegmentRenderer};
+    use segment::{boxed_info, BoxFuture, Check};
+    use std::fs;
+
+    /// Builds empty info after `delay`.
+    #[derive(Debug)]
+    struct Slow(Duration);
+
+    impl SegmentBuilder for Slow {
+        fn build(&self) -> BoxFuture<'_, Result<Box<dyn SegmentInfo>>> {
+            Box::pin(async move {
+                tokio::time::sleep(self.0).await;
+                Ok(boxed_info::<ChangesInfo, ChangesSegmentRenderer>(
+                    ChangesInfo::default(),
+                ))
+            })
+        }
+
+        fn restore(&self, _value: serde_json::Value) -> Result<Box<dyn SegmentInfo>> {
+            Err(anyhow!("nothing to restore"))
+        }
+
+        fn preflight(&self) -> Vec<Check> {
+            Vec::new()
+        }
+
+        fn refresh_interval(&self) -> Option<Duration> {
+            None
+        }
+    }
+
+    fn segment(name: &str, delay: Duration, on_error: OnError) -> ConfiguredSegment {
+        ConfiguredSegment {
+            name: name.to_string(),
+            builder: Arc::new(Slow(delay)),
+            on_error,
+            timeout: Some(Duration::from_millis(50)),
+            cache: None,
+            watch_interval: None,
+        }
+    }
+
+    /// Run `future` like `main` does, without waiting on builders that time out.
+    fn run<T>(future: impl std::future::Future<Output = T>) -> T {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let output = runtime.block_on(future);
+        runtime.shutdown_background();
+        output
+    }
+
+    fn build(segments: Vec<ConfiguredSegment>, budget: Option<Duration>) -> Result<Built> {
+        run(build_segments(segments, budget, None))
+    }
+
+    #[test]
+    fn timed_out_segments_follow_on_error() {
+        let slow = Duration::from_secs(10);
+        let built = build(
+            vec![
+                segment("shown", slow, OnError::Show),
+                segment("hidden", slow, OnError::Hide),
+                segment("fast", Duration::ZERO, OnError::Show),
+            ],
+            None,
+        )
+        .unwrap();
+
+        let reasons: Vec<_> = built
+            .segments
+            .iter()
+            .map(|segment| segment.info.as_ref().err().cloned())
+            .collect();
+        assert_eq!(
+            reasons,
+            [
+                Some("timed out after 50ms".to_string()),
+                Some("timed out after 50ms".to_string()),
+                None
+            ]
+        );
+        // The hidden one is left out, the shown one says it's unavailable
+        assert_eq!(built.into_renderers().len(), 2);
+
+        let failed = build(vec![segment("failing", slow, OnError::Fail)], None);
+        assert!(format!("{:#}", failed.unwrap_err()).contains("Segment `failing` failed"));
+    }
+
+    #[test]
+    fn refreshing_gives_up_on_slow_segments() {
+        let directory =
+            std::env::temp_dir().join(format!("welcome2u-refresh-{}", std::process::id()));
+        let cache = Cache::in_directory(directory.clone());
+        let cached = |name, delay| ConfiguredSegment {
+            cache: Some(CachePolicy {
+                ttl: Duration::from_secs(60),
+                refresh: CacheRefresh::Background,
+                fingerprint: String::new(),
+            }),
+            ..segment(name, delay, OnError::Show)
+        };
+
+        let started = std::time::Instant::now();
+        run(refresh_cache(
+            vec![
+                cached("slow", Duration::from_secs(10)),
+                cached("fast", Duration::ZERO),
+            ],
+            &cache,
+        ));
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert!(cache.load("slow", "").is_none());
+        assert!(cache.load("fast", "").is_some());
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn overall_budget_cuts_segments_short() {
+        let mut segment = segment("slow", Duration::from_secs(10), OnError::Show);
+        segment.timeout = None;
+        let built = build(vec![segment], Some(Duration::from_millis(20))).unwrap();
+        assert_eq!(
+            built.segments[0].info.as_ref().err().map(String::as_str),
+            Some("timed out after 20ms")
+        );
+    }
+}
@@ -0,0 +1,229 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::debug;
+
+use crate::paths;
+
+/// Segment info saved between runs, as one JSON file per segment.
+#[derive(Debug)]
+pub struct Cache {
+    directory: PathBuf,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Entry {
+    /// Seconds since the Unix epoch.
+    created_at: u64,
+    fingerprint: String,
+    info: serde_json::Value,
+}
+
+#[derive(Debug)]
+pub struct Cached {
+    pub age: Duration,
+    pub info: serde_json::Value,
+}
+
+impl Cache {
+    /// The cache under `$XDG_CACHE_HOME/welcome2u`, if there is a home directory to put it in.
+    pub fn open() -> Option<Self> {
+        paths::cache_dir().map(|directory| Self { directory })
+    }
+
+    /// A cache in `directory`, instead of the user's.
+    #[cfg(test)]
+    pub fn in_directory(directory: PathBuf) -> Self {
+        Self { directory }
+    }
+
+    fn path(&self, name: &str) -> PathBuf {
+        self.directory.join(format!("{name}.json"))
+    }
+
+    /// The info cached for `name`, unless it is missing, unreadable, or was built with
+    /// different options.
+    pub fn load(&self, name: &str, fingerprint: &str) -> Option<Cached> {
+        let path = self.path(name);
+        let entry: Entry = match fs::read(&path).map(|bytes| serde_json::from_slice(&bytes)) {
+            Ok(Ok(entry)) => entry,
+            Ok(Err(error)) => {
+                debug!(
+                    "ignoring unreadable cache entry {}: {error}",
+                    path.display()
+                );
+                return None;
+            }
+            Err(_) => return None,
+        };
+
+        if entry.fingerprint != fingerprint {
+            debug!("ignoring cache entry for `{name}` built with different options");
+            return None;
+        }
+
+        let created_at = UNIX_EPOCH + Duration::from_secs(entry.created_at);
+        Some(Cached {
+            age: SystemTime::now()
+                .duration_since(created_at)
+                .unwrap_or_default(),
+            info: entry.info,
+        })
+    }
+
+    pub fn store(&self, name: &str, fingerprint: &str, info: serde_json::Value) -> Result<()> {
+        let entry = Entry {
+            created_at: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+            fingerprint: fingerprint.to_string(),
+            info,
+        };
+
+        fs::create_dir_all(&self.directory)
+            .with_context(|| format!("Failed to create {}", self.directory.display()))?;
+
+        // Write to a temporary file first so a concurrent login never reads half an entry
+        let path = self.path(name);
+        let temporary = path.with_extension(format!("json.{}", std::process::id()));
+        fs::write(&temporary, serde_json::to_vec(&entry)?)
+            .with_context(|| format!("Failed to write {}", temporary.display()))?;
+        fs::rename(&temporary, &path)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+
+        Ok(())
+    }
+}
+
+/// Build `names` again in a detached copy of this process, so the next login finds fresh info
+/// without this one waiting for it.
+pub fn refresh_in_background(config: Option<&Path>, names: &[String]) -> Result<()> {
+    let mut command = Command::new(std::env::current_exe()?);
+    if let Some(config) = config {
+        command.arg("--config").arg(config);
+    }
+    command
+        .arg("refresh-cache")
+        .args(names)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .context("Failed to start background cache refresh")?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::build::build_segments;
+    use crate::config::{CacheRefresh, Config};
+
+    fn cache(test: &str) -> Cache {
+        Cache {
+            directory: std::env::temp_dir()
+                .join(format!("welcome2u-cache-{test}-{}", std::process::id())),
+        }
+    }
+
+    fn config(toml: &str) -> Config {
+        toml::from_str(toml).unwrap()
+    }
+
+    /// Store `info` for `name` as if it had been built `age` ago.
+    fn store_aged(cache: &Cache, name: &str, fingerprint: &str, info: serde_json::Value, age: u64) {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+        let entry = Entry {
+            created_at: now.as_secs() - age,
+            fingerprint: fingerprint.to_string(),
+            info,
+        };
+        fs::create_dir_all(&cache.directory).unwrap();
+        fs::write(cache.path(name), serde_json::to_vec(&entry).unwrap()).unwrap();
+    }
+
+    #[test]
+    fn entries_are_only_loaded_with_their_fingerprint() {
+        let cache = cache("fingerprint");
+        cache
+            .store("uptime", "a", serde_json::json!({ "seconds": 60 }))
+            .unwrap();
+
+        let cached = cache.load("uptime", "a").unwrap();
+        assert_eq!(cached.info["seconds"], 60);
+        assert!(cached.age < Duration::from_secs(5));
+        assert!(cache.load("uptime", "b").is_none());
+        assert!(cache.load("load", "a").is_none());
+
+        fs::remove_dir_all(&cache.directory).unwrap();
+    }
+
+    #[test]
+    fn fingerprints_change_with_segment_options_only() {
+        let registry = crate::registry();
+        let fingerprint = |toml: &str| {
+            let segments = config(toml).segments(&registry).unwrap();
+            segments[0].cache.as_ref().unwrap().fingerprint.clone()
+        };
+
+        let quote = fingerprint(
+            "segments = [\"quote\"]\n[segment.quote]\ncache_ttl = \"1h\"\nfortune = \"a\"",
+        );
+        let other_fortune = fingerprint(
+            "segments = [\"quote\"]\n[segment.quote]\ncache_ttl = \"1h\"\nfortune = \"b\"",
+        );
+        let other_ttl = fingerprint(
+            "segments = [\"quote\"]\n[segment.quote]\ncache_ttl = \"2h\"\nfortune = \"a\"",
+        );
+        assert_ne!(quote, other_fortune);
+        assert_eq!(quote, other_ttl);
+    }
+
+    #[test]
+    fn expired_entries_are_rebuilt_or_refreshed_later() {
+        let cache = cache("ttl");
+        let registry = crate::registry();
+        let cached_info = serde_json::json!({ "changes": [], "elapsed_seconds": 5 });
+        let built_info = |refresh: CacheRefresh, age: u64| {
+            let mut segments =
+                config("segments = [\"changes\"]\n[segment.changes]\ncache_ttl = \"1h\"")
+                    .segments(&registry)
+                    .unwrap();
+            let policy = segments[0].cache.as_mut().unwrap();
+            policy.refresh = refresh;
+            store_aged(
+                &cache,
+                "changes",
+                &policy.fingerprint,
+                cached_info.clone(),
+                age,
+            );
+
+            let runtime = tokio::runtime::Runtime::new().unwrap();
+            let built = runtime
+                .block_on(build_segments(segments, None, Some(&cache)))
+                .unwrap();
+            let info = built.segments[0].info.as_ref().unwrap().to_json().unwrap();
+            (info, built.stale)
+        };
+
+        // Fresh entries are used as they are
+        let (info, stale) = built_info(CacheRefresh::Inline, 600);
+        assert_eq!(info, cached_info);
+        assert!(stale.is_empty());
+
+        // Expired ones are built again before showing...
+        let (info, stale) = built_info(CacheRefresh::Inline, 7200);
+        assert_ne!(info, cached_info);
+        assert!(stale.is_empty());
+
+        // ...or shown once more while they're refreshed in the background
+        let (info, stale) = built_info(CacheRefresh::Background, 7200);
+        assert_eq!(info, cached_info);
+        assert_eq!(stale, ["changes"]);
+
+        fs::remove_dir_all(&cache.directory).unwrap();
+    }
+}
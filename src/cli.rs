@@ -0,0 +1,134 @@
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use segment::ColorDepth;
+use std::path::PathBuf;
+
+use crate::render::Format;
+
+#[derive(Debug, Parser)]
+#[command(version, about)]
+pub struct Cli {
+    /// Read configuration from this file instead of ~/.config/welcome2u/config.toml
+    #[arg(long, value_name = "PATH", global = true)]
+    pub config: Option<PathBuf>,
+
+    /// How to print the MOTD
+    #[arg(long, value_enum, default_value_t, global = true)]
+    pub format: Format,
+
+    /// Lay out the MOTD for this many columns instead of the terminal's width
+    #[arg(long, value_name = "COLUMNS", global = true)]
+    pub width: Option<u16>,
+
+    /// Print without colors, like --colors none
+    #[arg(long, global = true)]
+    pub no_color: bool,
+
+    /// How many colors the terminal can show; `auto` checks NO_COLOR, COLORTERM and TERM
+    #[arg(long, value_enum, default_value_t, global = true)]
+    pub colors: Colors,
+
+    /// Read host data (load, memory, disks, sensors, uptime) from a snapshot saved by
+    /// `capture-system` instead of this host
+    #[arg(long, value_name = "PATH", global = true)]
+    pub system: Option<PathBuf>,
+
+    /// Choose quotes, headings and fonts from this seed instead of at random, so the MOTD can
+    /// be reproduced; the no-repeat history is neither read nor updated
+    #[arg(long, value_name = "NUMBER", global = true)]
+    pub seed: Option<u64>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum Colors {
+    #[default]
+    Auto,
+    None,
+    #[value(name = "16")]
+    Ansi16,
+    #[value(name = "256")]
+    Ansi256,
+    Truecolor,
+}
+
+impl Colors {
+    pub fn depth(self) -> ColorDepth {
+        match self {
+            Colors::Auto => ColorDepth::detect(),
+            Colors::None => ColorDepth::None,
+            Colors::Ansi16 => ColorDepth::Ansi16,
+            Colors::Ansi256 => ColorDepth::Ansi256,
+            Colors::Truecolor => ColorDepth::TrueColor,
+        }
+    }
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Show the MOTD (the default)
+    Show(ShowArgs),
+    /// Print a one-line summary like `load 0.42 · mem 63% · / 81%`, e.g. for tmux's
+    /// status-right or a starship custom module
+    Summary(ShowArgs),
+    /// Show the MOTD fullscreen and keep it up to date, building each segment again at its
+    /// `watch_interval`; press q to quit or r to refresh everything
+    Watch(ShowArgs),
+    /// List the available segments and whether they're enabled
+    ListSegments,
+    /// Check what each segment depends on and explain how to fix what's missing
+    Doctor,
+    /// Print this host's data (load, memory, disks, sensors, uptime) as JSON, to reproduce its
+    /// MOTD elsewhere with --system
+    CaptureSystem,
+    /// Work with the configuration
+    #[command(subcommand)]
+    Config(ConfigCommand),
+    /// Work with fortune databases
+    #[command(subcommand)]
+    Fortune(FortuneCommand),
+    /// Build segments and update their cache entries; run in the background after a login
+    /// that showed stale info
+    #[command(hide = true)]
+    RefreshCache { segments: Vec<String> },
+}
+
+#[derive(Debug, Default, Args)]
+pub struct ShowArgs {
+    /// Show only these segments, in this order, whether or not they're enabled
+    #[arg(long, value_name = "SEGMENTS", value_delimiter = ',')]
+    pub only: Vec<String>,
+
+    /// Leave out these segments
+    #[arg(long, value_name = "SEGMENTS", value_delimiter = ',')]
+    pub skip: Vec<String>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ConfigCommand {
+    /// Load the configuration and check every segment's options
+    Check,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum FortuneCommand {
+    /// Write a strfile `.dat` index next to each database, so a fortune can be read from it
+    /// without scanning the whole file; run again whenever a database changes
+    Index {
+        #[arg(required = true, value_name = "DATABASE")]
+        databases: Vec<PathBuf>,
+
+        /// Sort the fortunes in the index alphabetically, like `strfile -o`
+        #[arg(long, conflicts_with = "random")]
+        ordered: bool,
+
+        /// Shuffle the fortunes in the index, like `strfile -r`
+        #[arg(long)]
+        random: bool,
+
+        /// Mark the database as rot13-encoded, like offensive ones, like `strfile -x`
+        #[arg(long)]
+        rot13: bool,
+    },
+}
@@ -0,0 +1,318 @@
+use anyhow::{Context, Result};
+use segment::{LayoutConfig, Registry, RegistryError, SegmentBuilder, ThemeName};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use std::{fs, io};
+
+use crate::paths;
+use crate::throttle::ThrottleConfig;
+
+/// Segments shown when the configuration doesn't list any, top to bottom.
+pub const DEFAULT_SEGMENTS: &[&str] = &[
+    "heading",
+    "quote",
+    "user",
+    "ip",
+    "os",
+    "uptime",
+    "load",
+    "temperatures",
+    "updates",
+    "disk",
+    "memory",
+    "docker",
+];
+
+const SYSTEM_CONFIG: &str = "/etc/welcome2u/config.toml";
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Segments to show, top to bottom.
+    #[serde(default = "default_segments")]
+    pub segments: Vec<String>,
+    /// Overall budget for building segments; anything still running afterwards is treated as
+    /// failed so the MOTD prints anyway.
+    #[serde(default, with = "humantime_serde")]
+    pub timeout: Option<Duration>,
+    /// Built-in theme to draw the MOTD with.
+    #[serde(default)]
+    pub theme: ThemeName,
+    /// How segments flow into columns on wide terminals.
+    #[serde(default)]
+    pub layout: LayoutConfig,
+    /// When a bare `welcome2u` shows the full MOTD rather than a short one or nothing; every
+    /// run shows it in full when there's no `[throttle]` table.
+    #[serde(default)]
+    pub throttle: Option<ThrottleConfig>,
+    /// Settings for each segment, from its `[segment.<name>]` table.
+    #[serde(default, rename = "segment")]
+    pub segment_configs: BTreeMap<String, SegmentConfig>,
+    /// Files the configuration was loaded from, lowest precedence first.
+    #[serde(skip)]
+    pub sources: Vec<PathBuf>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SegmentConfig {
+    /// What to do when the segment fails to build.
+    #[serde(default)]
+    pub on_error: OnError,
+    /// How long the segment may take to build before it is treated as failed.
+    #[serde(default, with = "humantime_serde")]
+    pub timeout: Option<Duration>,
+    /// Reuse the segment's last info for this long instead of building it again.
+    #[serde(default, with = "humantime_serde")]
+    pub cache_ttl: Option<Duration>,
+    /// What to do once the cached info is older than `cache_ttl`.
+    #[serde(default)]
+    pub cache_refresh: CacheRefresh,
+    /// How often `welcome2u watch` builds the segment again, instead of its own default.
+    #[serde(default, with = "humantime_serde")]
+    pub watch_interval: Option<Duration>,
+    /// Every other key is an option for the segment's builder.
+    #[serde(flatten)]
+    pub options: toml::Table,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum OnError {
+    /// Leave the segment out of the MOTD.
+    #[default]
+    Hide,
+    /// Show a dim "unavailable: <reason>" row in its place.
+    Show,
+    /// Abort the whole MOTD.
+    Fail,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CacheRefresh {
+    /// Build the segment again before showing it.
+    #[default]
+    Inline,
+    /// Show the stale info right away and build it again in the background for next time.
+    Background,
+}
+
+#[derive(Debug, Clone)]
+pub struct CachePolicy {
+    pub ttl: Duration,
+    pub refresh: CacheRefresh,
+    /// The segment's options, so cached info is thrown away when they change.
+    pub fingerprint: String,
+}
+
+/// A segment ready to build, along with the settings the binary applies around it.
+#[derive(Debug, Clone)]
+pub struct ConfiguredSegment {
+    pub name: String,
+    pub builder: Arc<dyn SegmentBuilder>,
+    pub on_error: OnError,
+    pub timeout: Option<Duration>,
+    pub cache: Option<CachePolicy>,
+    /// How often `welcome2u watch` builds it again, if ever.
+    pub watch_interval: Option<Duration>,
+}
+
+fn default_segments() -> Vec<String> {
+    DEFAULT_SEGMENTS
+        .iter()
+        .map(|name| name.to_string())
+        .collect()
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            segments: default_segments(),
+            timeout: None,
+            theme: ThemeName::default(),
+            layout: LayoutConfig::default(),
+            throttle: None,
+            segment_configs: BTreeMap::new(),
+            sources: Vec::new(),
+        }
+    }
+}
+
+impl Config {
+    /// Load the system configuration, then layer the user's configuration (or `path`, if given)
+    /// on top of it. Missing files are skipped, except for an explicit `path`.
+    pub fn load(path: Option<&Path>) -> Result<Self> {
+        let mut layers = vec![(PathBuf::from(SYSTEM_CONFIG), false)];
+        match path {
+            Some(path) => layers.push((path.to_path_buf(), true)),
+            None => layers.extend(paths::config_dir().map(|dir| (dir.join("config.toml"), false))),
+        }
+        Self::load_layers(layers)
+    }
+
+    /// Load and merge `layers`, lowest precedence first, each with whether it must exist.
+    fn load_layers(layers: Vec<(PathBuf, bool)>) -> Result<Self> {
+        let mut merged = toml::Table::new();
+        let mut sources = Vec::new();
+        for (path, required) in layers {
+            if let Some(layer) = read_layer(&path, required)? {
+                merge(&mut merged, layer);
+                sources.push(path);
+            }
+        }
+
+        let config: Self = toml::Value::Table(merged)
+            .try_into()
+            .context("Invalid configuration")?;
+        Ok(Self { sources, ..config })
+    }
+
+    /// Narrow the enabled segments to `only` (in that order) when it isn't empty, minus `skip`.
+    pub fn select(&mut self, registry: &Registry, only: &[String], skip: &[String]) -> Result<()> {
+        if let Some(name) = only
+            .iter()
+            .chain(skip)
+            .find(|name| !registry.contains(name))
+        {
+            return Err(registry.unknown_segment(name).into());
+        }
+
+        if !only.is_empty() {
+            self.segments = only.to_vec();
+        }
+        self.segments.retain(|name| !skip.contains(name));
+        Ok(())
+    }
+
+    pub fn segment_config(&self, name: &str) -> SegmentConfig {
+        self.segment_configs.get(name).cloned().unwrap_or_default()
+    }
+
+    /// Create the builder for segment `name` from its configured options.
+    pub fn builder(
+        &self,
+        registry: &Registry,
+        name: &str,
+    ) -> Result<Box<dyn SegmentBuilder>, RegistryError> {
+        registry.create(name, &self.segment_config(name).options)
+    }
+
+    /// Create a builder for every configured segment, validating names and options up front.
+    pub fn segments(&self, registry: &Registry) -> Result<Vec<ConfiguredSegment>> {
+        // Options for disabled segments are checked too, so typos don't hide until enabled
+        for (name, segment_config) in &self.segment_configs {
+            registry.create(name, &segment_config.options)?;
+        }
+
+        self.segments
+            .iter()
+            .map(|name| {
+                let segment_config = self.segment_config(name);
+                let cache = segment_config.cache_ttl.map(|ttl| CachePolicy {
+                    ttl,
+                    refresh: segment_config.cache_refresh,
+                    fingerprint: segment_config.options.to_string(),
+                });
+                let builder: Arc<dyn SegmentBuilder> = self.builder(registry, name)?.into();
+                Ok(ConfiguredSegment {
+                    name: name.clone(),
+                    on_error: segment_config.on_error,
+                    timeout: segment_config.timeout,
+                    cache,
+                    watch_interval: segment_config
+                        .watch_interval
+                        .or_else(|| builder.refresh_interval()),
+                    builder,
+                })
+            })
+            .collect()
+    }
+}
+
+fn read_layer(path: &Path, required: bool) -> Result<Option<toml::Table>> {
+    match fs::read_to_string(path) {
+        Ok(contents) => toml::from_str(&contents)
+            .map(Some)
+            .with_context(|| format!("Failed to parse {}", path.display())),
+        Err(error) if error.kind() == io::ErrorKind::NotFound && !required => Ok(None),
+        Err(error) => Err(error).with_context(|| format!("Failed to read {}", path.display())),
+    }
+}
+
+/// Merge `layer` into `base`, recursing into tables so a layer only overrides the keys it sets.
+fn merge(base: &mut toml::Table, layer: toml::Table) {
+    for (key, value) in layer {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base_table)), toml::Value::Table(layer_table)) => {
+                merge(base_table, layer_table)
+            }
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Write each of `layers` to a file in a fresh directory, lowest precedence first.
+    fn write_layers(test: &str, layers: &[&str]) -> (PathBuf, Vec<(PathBuf, bool)>) {
+        let dir = std::env::temp_dir().join(format!("welcome2u-{test}-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let paths = layers
+            .iter()
+            .enumerate()
+            .map(|(index, contents)| {
+                let path = dir.join(format!("{index}.toml"));
+                fs::write(&path, contents).unwrap();
+                (path, false)
+            })
+            .collect();
+        (dir, paths)
+    }
+
+    #[test]
+    fn user_layer_overrides_system_keys_it_sets() {
+        let system = r#"
+            segments = ["quote", "load"]
+            timeout = "2s"
+            [segment.quote]
+            fortune = "collected-quotes"
+            cache_ttl = "1h"
+        "#;
+        let user = r#"
+            timeout = "500ms"
+            [segment.quote]
+            fortune = "pickleisms"
+        "#;
+        let (dir, mut layers) = write_layers("layers", &[system, user]);
+        layers.push((dir.join("missing.toml"), false));
+
+        let config = Config::load_layers(layers).unwrap();
+        assert_eq!(config.segments, ["quote", "load"]);
+        assert_eq!(config.timeout, Some(Duration::from_millis(500)));
+        let quote = config.segment_config("quote");
+        assert_eq!(quote.cache_ttl, Some(Duration::from_secs(3600)));
+        assert_eq!(quote.options["fortune"].as_str(), Some("pickleisms"));
+        assert_eq!(config.sources.len(), 2);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn unknown_keys_in_any_layer_are_rejected() {
+        let (dir, layers) = write_layers("unknown", &["timeout = \"1s\"", "segmnts = []"]);
+        let error = Config::load_layers(layers).unwrap_err();
+        assert!(format!("{error:#}").contains("unknown field `segmnts`"));
+
+        // An explicit --config has to exist
+        assert!(Config::load_layers(vec![(dir.join("missing.toml"), true)]).is_err());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
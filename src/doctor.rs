@@ -0,0 +1,104 @@
+use anyhow::{bail, Result};
+use segment::{Check, CheckStatus, Registry};
+use serde::Serialize;
+
+use crate::config::Config;
+use crate::print;
+use crate::render::Format;
+
+#[derive(Debug, Serialize)]
+struct Diagnosis {
+    segment: String,
+    checks: Vec<Check>,
+}
+
+/// Run the preflight checks of every enabled segment and print what's wrong with each,
+/// failing when any check doesn't pass.
+pub fn doctor(config: &Config, registry: &Registry, format: Format) -> Result<()> {
+    let diagnoses: Vec<_> = config
+        .segments
+        .iter()
+        .map(|name| {
+            let checks = match config.builder(registry, name) {
+                Ok(builder) => builder.preflight(),
+                Err(error) => vec![Check::new(
+                    "options",
+                    CheckStatus::Failed(format!("{:#}", anyhow::Error::from(error))),
+                )
+                .with_hint(format!(
+                    "Fix `segments` or [segment.{name}] in the configuration"
+                ))],
+            };
+            Diagnosis {
+                segment: name.clone(),
+                checks,
+            }
+        })
+        .collect();
+
+    match format {
+        Format::Json => print(&(serde_json::to_string_pretty(&diagnoses)? + "\n"))?,
+        _ => print(&to_table(&diagnoses))?,
+    }
+
+    let problems = diagnoses
+        .iter()
+        .flat_map(|diagnosis| &diagnosis.checks)
+        .filter(|check| !check.passed())
+        .count();
+    if problems > 0 {
+        bail!("Found {problems} problem(s)");
+    }
+
+    Ok(())
+}
+
+fn to_table(diagnoses: &[Diagnosis]) -> String {
+    let segment_width = diagnoses
+        .iter()
+        .map(|diagnosis| diagnosis.segment.len())
+        .max()
+        .unwrap_or(0);
+    let status_width = diagnoses
+        .iter()
+        .flat_map(|diagnosis| &diagnosis.checks)
+        .map(|check| status_label(&check.status).len())
+        .max()
+        .unwrap_or(0)
+        .max("OK".len());
+    let indent = segment_width + status_width + 4;
+
+    let mut table = String::new();
+    for diagnosis in diagnoses {
+        if diagnosis.checks.is_empty() {
+            table.push_str(&format!(
+                "{:segment_width$}  {:status_width$}  nothing to check\n",
+                diagnosis.segment, "OK"
+            ));
+        }
+
+        for (index, check) in diagnosis.checks.iter().enumerate() {
+            // Only name the segment on its first row, so each segment reads as a group
+            let segment = if index == 0 { &diagnosis.segment } else { "" };
+            table.push_str(&format!(
+                "{segment:segment_width$}  {:status_width$}  {}\n",
+                status_label(&check.status),
+                check.subject
+            ));
+            if let CheckStatus::Failed(message) = &check.status {
+                table.push_str(&format!("{:indent$}{message}\n", ""));
+            }
+            if let (false, Some(hint)) = (check.passed(), &check.hint) {
+                table.push_str(&format!("{:indent$}hint: {hint}\n", ""));
+            }
+        }
+    }
+    table
+}
+
+fn status_label(status: &CheckStatus) -> String {
+    match status {
+        CheckStatus::Failed(_) => "failed".to_string(),
+        status => status.to_string(),
+    }
+}
@@ -0,0 +1,151 @@
+//! Each segment's info as it was last shown, so the `changes` segment can say what's different.
+
+use changes::{ChangesInfo, ChangesSegmentRenderer};
+use segment::boxed_info;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::warn;
+
+use crate::build::{Built, BuiltSegment};
+use crate::state;
+
+const HISTORY_FILE: &str = "history.json";
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct History {
+    segments: BTreeMap<String, Shown>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Shown {
+    /// When the info was shown, in seconds since the Unix epoch.
+    shown_at: u64,
+    info: serde_json::Value,
+}
+
+/// Fill in the `changes` segment, when it's one of `built`, by comparing every other segment
+/// with what it showed last time, then remember what they show now. Segments that failed
+/// keep what they showed before, so they're compared with that once they're back, and so do
+/// segments shown from the cache: their info is as old as the entry, so comparing it as if it
+/// were built just now would report changes that didn't happen, like a reboot.
+pub fn record(built: &mut Built) {
+    update(built, true);
+}
+
+/// Like [`record`], but without remembering what the segments show now: the one-line summary
+/// is shown too often, e.g. in a tmux status line, and too briefly to count as having seen
+/// the changes.
+pub fn compare(built: &mut Built) {
+    update(built, false);
+}
+
+fn update(built: &mut Built, remember: bool) {
+    let Some(index) = built
+        .segments
+        .iter()
+        .position(|segment| segment.name == "changes")
+    else {
+        return;
+    };
+
+    let mut history: History = state::load(HISTORY_FILE);
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let changes = history.update(&built.segments, now);
+
+    if remember {
+        if let Err(error) = state::save(HISTORY_FILE, &history) {
+            warn!("{error:#}");
+        }
+    }
+
+    built.segments[index].info = Ok(boxed_info::<ChangesInfo, ChangesSegmentRenderer>(changes));
+}
+
+impl History {
+    /// Compare `segments` with what they showed last time and remember what they show `now`.
+    fn update(&mut self, segments: &[BuiltSegment], now: u64) -> ChangesInfo {
+        let mut changes = Vec::new();
+        let mut last_shown = None;
+        for segment in segments {
+            let Ok(info) = &segment.info else {
+                continue;
+            };
+            if segment.name == "changes" || segment.cached {
+                continue;
+            }
+            let value = match info.to_json() {
+                Ok(value) => value,
+                Err(error) => {
+                    warn!(
+                        segment = segment.name.as_str(),
+                        "failed to record: {error:#}"
+                    );
+                    continue;
+                }
+            };
+
+            let shown = Shown {
+                shown_at: now,
+                info: value,
+            };
+            if let Some(previous) = self.segments.insert(segment.name.clone(), shown) {
+                let elapsed = Duration::from_secs(now.saturating_sub(previous.shown_at));
+                changes.extend(info.changes(previous.info, elapsed));
+                last_shown = last_shown.max(Some(previous.shown_at));
+            }
+        }
+
+        let elapsed = last_shown.map(|shown_at| Duration::from_secs(now.saturating_sub(shown_at)));
+        ChangesInfo::new(changes, elapsed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::OnError;
+    use serde_json::json;
+    use uptime::{UptimeInfo, UptimeSegmentRenderer};
+
+    fn uptime(seconds: u64, cached: bool) -> BuiltSegment {
+        let info: UptimeInfo = serde_json::from_value(json!({ "seconds": seconds })).unwrap();
+        BuiltSegment {
+            name: "uptime".to_string(),
+            info: Ok(boxed_info::<UptimeInfo, UptimeSegmentRenderer>(info)),
+            on_error: OnError::Show,
+            cached,
+        }
+    }
+
+    #[test]
+    fn cached_info_is_not_compared() {
+        let changes = |history: &mut History, segment, now| {
+            serde_json::to_value(history.update(&[segment], now)).unwrap()
+        };
+
+        // Shown half an hour ago, after ten minutes up
+        let mut history = History::default();
+        history.update(&[uptime(600, false)], 1_000);
+
+        // A cached entry from then still says ten minutes, which would look like a reboot since
+        assert_eq!(
+            changes(&mut history, uptime(600, true), 2_800),
+            json!({ "changes": [], "elapsed_seconds": null })
+        );
+        assert_eq!(history.segments["uptime"].shown_at, 1_000);
+
+        assert_eq!(
+            changes(&mut history, uptime(2_400, false), 2_800),
+            json!({ "changes": [], "elapsed_seconds": 1_800 })
+        );
+        assert_eq!(
+            changes(&mut history, uptime(60, false), 3_000)["changes"][0]["text"],
+            "rebooted since last login"
+        );
+    }
+}
@@ -0,0 +1,55 @@
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::build::Built;
+use crate::print;
+
+/// Bumped whenever a segment's fields change in a way that could break consumers.
+const SCHEMA_VERSION: u32 = 1;
+
+#[derive(Debug, Serialize)]
+struct Report {
+    schema_version: u32,
+    segments: Vec<SegmentReport>,
+}
+
+#[derive(Debug, Serialize)]
+struct SegmentReport {
+    name: String,
+    #[serde(flatten)]
+    result: SegmentResult,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+enum SegmentResult {
+    Ok { data: serde_json::Value },
+    Error { error: String },
+}
+
+/// Print the info collected by every segment, including the ones that failed, as JSON.
+pub fn print_json(built: Built) -> Result<()> {
+    let segments = built
+        .segments
+        .into_iter()
+        .map(|segment| {
+            let data = segment
+                .info
+                .and_then(|info| info.to_json().map_err(|error| format!("{error:#}")));
+            SegmentReport {
+                name: segment.name,
+                result: match data {
+                    Ok(data) => SegmentResult::Ok { data },
+                    Err(error) => SegmentResult::Error { error },
+                },
+            }
+        })
+        .collect();
+
+    let report = Report {
+        schema_version: SCHEMA_VERSION,
+        segments,
+    };
+
+    print(&(serde_json::to_string_pretty(&report)? + "\n"))
+}
@@ -1,9 +1,10 @@
-use anyhow::Result;
-use ratatui::layout::*;
-use ratatui::{backend::CrosstermBackend, *};
-use std::io::stdout;
-use tokio;
-use tracing::{info, instrument, span};
+use anyhow::{Context, Result};
+use clap::Parser;
+use fortune::index::{self, Index};
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+use tracing::{info, instrument, span, warn};
 use tracing_appender::rolling::{RollingFileAppender, Rotation};
 use tracing_flame::FlameLayer;
 use tracing_subscriber::fmt::format::FmtSpan;
@@ -13,180 +14,64 @@ use tracing_subscriber::EnvFilter;
 
 use segment::*;
 
-#[instrument()]
-async fn build_segments() -> Result<(
-    heading::HeadingSegmentRenderer,
-    quote::QuoteSegmentRenderer,
-    user::UserSegmentRenderer,
-    ip::IpSegmentRenderer,
-    os::OsSegmentRenderer,
-    uptime::UptimeSegmentRenderer,
-    load::LoadSegmentRenderer,
-    temperatures::TemperaturesSegmentRenderer,
-    disk::DiskSegmentRenderer,
-    memory::MemorySegmentRenderer,
-    docker::DockerSegmentRenderer,
-    updates::UpdatesSegmentRenderer,
-)> {
-    // Create async tasks for building segment info
-    let heading_info_future =
-        tokio::spawn(async { heading::HeadingSegmentInfoBuilder::default().build().await });
-    let quote_info_future =
-        tokio::spawn(async { quote::QuoteInfoBuilder::default().build().await });
-    let user_info_future = tokio::spawn(async { user::UserInfoBuilder::default().build().await });
-    let ip_info_future = tokio::spawn(async { ip::IpInfoBuilder::default().build().await });
-    let os_info_future = tokio::spawn(async { os::OsInfoBuilder::default().build().await });
-    let uptime_info_future =
-        tokio::spawn(async { uptime::UptimeInfoBuilder::default().build().await });
-    let load_info_future = tokio::spawn(async { load::LoadInfoBuilder::default().build().await });
-    let temperatures_info_future = tokio::spawn(async {
-        temperatures::TemperaturesInfoBuilder::default()
-            .build()
-            .await
-    });
-    let disk_info_future = tokio::spawn(async {
-        disk::DiskInfoBuilder::default()
-            .exclude_mount_point("/System/Volumes/Data".to_string())
-            .build()
-            .await
-    });
-    let memory_info_future = tokio::spawn(async {
-        memory::MemoryInfoBuilder::default()
-            .warning_threshold_percent(80.0)
-            .critical_threshold_percent(90.0)
-            .build()
-            .await
-    });
-    let docker_info_future =
-        tokio::spawn(async { docker::DockerInfoBuilder::default().build().await });
-    let updates_info_future =
-        tokio::spawn(async { updates::UpdatesInfoBuilder::default().build().await });
-
-    // Wait for all futures to complete
-    let (
-        heading_info,
-        quote_info,
-        user_info,
-        ip_info,
-        os_info,
-        uptime_info,
-        load_info,
-        temperatures_info,
-        disk_info,
-        memory_info,
-        docker_info,
-        updates_info,
-    ) = tokio::try_join!(
-        heading_info_future,
-        quote_info_future,
-        user_info_future,
-        ip_info_future,
-        os_info_future,
-        uptime_info_future,
-        load_info_future,
-        temperatures_info_future,
-        disk_info_future,
-        memory_info_future,
-        docker_info_future,
-        updates_info_future
-    )?;
-
-    // Unwrap results and create renderers
-    Ok((
-        heading::HeadingSegmentRenderer::from(Box::new(heading_info?)),
-        quote::QuoteSegmentRenderer::from(Box::new(quote_info?)),
-        user::UserSegmentRenderer::from(Box::new(user_info?)),
-        ip::IpSegmentRenderer::from(Box::new(ip_info?)),
-        os::OsSegmentRenderer::from(Box::new(os_info?)),
-        uptime::UptimeSegmentRenderer::from(Box::new(uptime_info?)),
-        load::LoadSegmentRenderer::from(Box::new(load_info?)),
-        temperatures::TemperaturesSegmentRenderer::from(Box::new(temperatures_info?)),
-        disk::DiskSegmentRenderer::from(Box::new(disk_info?)),
-        memory::MemorySegmentRenderer::from(Box::new(memory_info?)),
-        docker::DockerSegmentRenderer::from(Box::new(docker_info?)),
-        updates::UpdatesSegmentRenderer::from(Box::new(updates_info?)),
-    ))
-}
+use build::{build_segments, install_panic_hook, refresh_cache, Built};
+use cache::Cache;
+use cli::{Cli, Command, ConfigCommand, FortuneCommand, ShowArgs};
+use config::Config;
+use doctor::doctor;
+use json::print_json;
+use render::{render_segments, render_summary, Format, RenderOptions};
+use throttle::Mode;
 
-async fn render_segments(
-    heading_renderer: heading::HeadingSegmentRenderer,
-    quote_renderer: quote::QuoteSegmentRenderer,
-    user_renderer: user::UserSegmentRenderer,
-    ip_renderer: ip::IpSegmentRenderer,
-    os_renderer: os::OsSegmentRenderer,
-    uptime_renderer: uptime::UptimeSegmentRenderer,
-    load_renderer: load::LoadSegmentRenderer,
-    temperatures_renderer: temperatures::TemperaturesSegmentRenderer,
-    updates_renderer: updates::UpdatesSegmentRenderer,
-    disk_renderer: disk::DiskSegmentRenderer,
-    memory_renderer: memory::MemorySegmentRenderer,
-    docker_renderer: docker::DockerSegmentRenderer,
-) -> Result<()> {
-    let backend = CrosstermBackend::new(stdout());
-
-    // Create constraints
-    let constraints = vec![
-        Constraint::Length(heading_renderer.height()),
-        Constraint::Length(quote_renderer.height()),
-        Constraint::Length(user_renderer.height()),
-        Constraint::Length(ip_renderer.height()),
-        Constraint::Length(os_renderer.height()),
-        Constraint::Length(uptime_renderer.height()),
-        Constraint::Length(load_renderer.height()),
-        Constraint::Length(temperatures_renderer.height()),
-        Constraint::Length(updates_renderer.height()),
-        Constraint::Length(disk_renderer.height()),
-        Constraint::Length(memory_renderer.height()),
-        Constraint::Length(docker_renderer.height()),
-    ];
-
-    let options = TerminalOptions {
-        viewport: Viewport::Inline(
-            constraints
-                .iter()
-                .map(|c| match c {
-                    Constraint::Length(l) => *l,
-                    _ => panic!("All constraints must be Constraint::Length"),
-                })
-                .sum(),
-        ),
-    };
+mod build;
+mod cache;
+mod cli;
+mod config;
+mod doctor;
+mod history;
+mod json;
+mod paths;
+mod render;
+mod state;
+mod throttle;
+mod watch;
 
-    let mut terminal = Terminal::with_options(backend, options)?;
-    terminal.draw(|frame| {
-        let layout = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints(constraints)
-            .split(frame.area());
-
-        heading_renderer.render(frame, layout[0]).unwrap();
-        quote_renderer.render(frame, layout[1]).unwrap();
-        user_renderer.render(frame, layout[2]).unwrap();
-        ip_renderer.render(frame, layout[3]).unwrap();
-        os_renderer.render(frame, layout[4]).unwrap();
-        uptime_renderer.render(frame, layout[5]).unwrap();
-        load_renderer.render(frame, layout[6]).unwrap();
-        temperatures_renderer.render(frame, layout[7]).unwrap();
-        updates_renderer.render(frame, layout[8]).unwrap();
-        disk_renderer.render(frame, layout[9]).unwrap();
-        memory_renderer.render(frame, layout[10]).unwrap();
-        docker_renderer.render(frame, layout[11]).unwrap();
-    })?;
+fn registry() -> Registry {
+    let mut registry = Registry::default();
+    heading::register(&mut registry);
+    quote::register(&mut registry);
+    user::register(&mut registry);
+    ip::register(&mut registry);
+    os::register(&mut registry);
+    uptime::register(&mut registry);
+    load::register(&mut registry);
+    temperatures::register(&mut registry);
+    updates::register(&mut registry);
+    disk::register(&mut registry);
+    memory::register(&mut registry);
+    docker::register(&mut registry);
+    changes::register(&mut registry);
+    command::register(&mut registry);
+    registry
+}
 
+fn print(text: &str) -> Result<()> {
+    let mut stdout = std::io::stdout().lock();
+    stdout.write_all(text.as_bytes())?;
+    stdout.flush()?;
     Ok(())
 }
 
-#[tokio::main]
-async fn main() -> Result<()> {
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+
     // Honor WELCOME2U environment variable for output control
-    // WELCOME2U=0 disables all output (program exits immediately)
+    // WELCOME2U=0 disables the MOTD (program exits immediately)
     // WELCOME2U=1 (or unset) enables normal behavior
-    let enabled = match std::env::var("WELCOME2U") {
-        Ok(value) if value == "0" => false,
-        _ => true,
-    };
+    let showing = matches!(cli.command, None | Some(Command::Show(_)));
+    let enabled = !matches!(std::env::var("WELCOME2U"), Ok(value) if value == "0");
 
-    if !enabled {
+    if showing && !enabled {
         return Ok(());
     }
 
@@ -221,54 +106,218 @@ async fn main() -> Result<()> {
             (None, None)
         };
 
-    main_inner().await?;
+    install_panic_hook();
+    let runtime = tokio::runtime::Runtime::new()?;
+    let result = runtime.block_on(main_inner(cli));
+    // Don't wait on builders that blew their time budget and are still running
+    runtime.shutdown_background();
 
     // Ensure the flame guard is dropped before the file appender guard
     drop(guard);
 
-    Ok(())
+    result
 }
 
 #[instrument(name = "main")]
-async fn main_inner() -> Result<()> {
+async fn main_inner(cli: Cli) -> Result<()> {
     info!("starting");
 
+    let mut config = Config::load(cli.config.as_deref())?;
+    let mut registry = registry();
+    registry
+        .set_seed(cli.seed)
+        .set_state_dir(paths::state_dir());
+    match &cli.system {
+        Some(path) => {
+            registry.set_system(SharedSystem::new(SystemSnapshot::load(path)?));
+        }
+        // Watch builds segments over and over, so every build has to read the host afresh
+        None if matches!(cli.command, Some(Command::Watch(_))) => {
+            registry.set_system(SharedSystem::new(SysinfoProvider));
+        }
+        None => {}
+    }
+
+    match &cli.command {
+        None => match config.throttle.as_ref().map(throttle::check) {
+            None | Some(Mode::Full) => show(&cli, config, &registry, &ShowArgs::default()).await,
+            Some(Mode::Compact) => summary(&cli, config, &registry, &ShowArgs::default()).await,
+            Some(Mode::Silent) => Ok(()),
+        },
+        Some(Command::Show(args)) => show(&cli, config, &registry, args).await,
+        Some(Command::Summary(args)) => summary(&cli, config, &registry, args).await,
+        Some(Command::Watch(args)) => {
+            config.select(&registry, &args.only, &args.skip)?;
+            let options = render_options(&cli, &config);
+            watch::watch(config.segments(&registry)?, config.timeout, &options).await
+        }
+        Some(Command::ListSegments) => list_segments(&config, &registry),
+        Some(Command::Doctor) => doctor(&config, &registry, cli.format),
+        Some(Command::CaptureSystem) => {
+            let snapshot = SystemSnapshot::capture(&SysinfoProvider);
+            print(&(serde_json::to_string_pretty(&snapshot)? + "\n"))
+        }
+        Some(Command::Config(ConfigCommand::Check)) => check_config(&config, &registry),
+        Some(Command::Fortune(FortuneCommand::Index {
+            databases,
+            ordered,
+            random,
+            rot13,
+        })) => {
+            let flags = index::Flags {
+                random: *random,
+                ordered: *ordered,
+                rotated: *rot13,
+            };
+            index_fortunes(databases, flags)
+        }
+        Some(Command::RefreshCache { segments: names }) => {
+            config.select(&registry, names, &[])?;
+            if let Some(cache) = Cache::open() {
+                refresh_cache(config.segments(&registry)?, &cache).await;
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Build the segments selected by `args`, from the cache where it's fresh enough.
+async fn build(
+    cli: &Cli,
+    config: &mut Config,
+    registry: &Registry,
+    args: &ShowArgs,
+) -> Result<Built> {
+    config.select(registry, &args.only, &args.skip)?;
+    let segments = config.segments(registry)?;
+    // Info built from a snapshot of another host mustn't end up in this host's cache, and
+    // seeded output has to be built afresh to be reproducible, without leaking into later runs
+    let cache = (cli.system.is_none() && cli.seed.is_none())
+        .then(Cache::open)
+        .flatten();
+
     let span = span!(tracing::Level::DEBUG, "build_segments");
     let _enter = span.enter();
-    let (
-        heading_renderer,
-        quote_renderer,
-        user_renderer,
-        ip_renderer,
-        os_renderer,
-        uptime_renderer,
-        load_renderer,
-        temperatures_renderer,
-        disk_renderer,
-        memory_renderer,
-        docker_renderer,
-        updates_renderer,
-    ) = build_segments().await?;
+    let built = build_segments(segments, config.timeout, cache.as_ref()).await?;
     drop(_enter);
 
+    if !built.stale.is_empty() {
+        if let Err(error) = cache::refresh_in_background(cli.config.as_deref(), &built.stale) {
+            warn!("{error:#}");
+        }
+    }
+
+    Ok(built)
+}
+
+fn render_options(cli: &Cli, config: &Config) -> RenderOptions {
+    RenderOptions {
+        format: cli.format,
+        theme: config.theme.theme(),
+        width: cli.width,
+        colors: if cli.no_color {
+            ColorDepth::None
+        } else {
+            cli.colors.depth()
+        },
+        layout: config.layout,
+    }
+}
+
+async fn show(cli: &Cli, mut config: Config, registry: &Registry, args: &ShowArgs) -> Result<()> {
+    let mut built = build(cli, &mut config, registry, args).await?;
+    // Another host's snapshot says nothing about what changed here
+    if cli.system.is_none() {
+        history::record(&mut built);
+    }
+
     let span = span!(tracing::Level::DEBUG, "render_segments");
     let _enter = span.enter();
-    let result = render_segments(
-        heading_renderer,
-        quote_renderer,
-        user_renderer,
-        ip_renderer,
-        os_renderer,
-        uptime_renderer,
-        load_renderer,
-        temperatures_renderer,
-        updates_renderer,
-        disk_renderer,
-        memory_renderer,
-        docker_renderer,
-    )
-    .await?;
+    match cli.format {
+        Format::Json => print_json(built)?,
+        _ => render_segments(&built.into_renderers(), &render_options(cli, &config))?,
+    }
     drop(_enter);
 
-    Ok(result)
+    Ok(())
+}
+
+async fn summary(
+    cli: &Cli,
+    mut config: Config,
+    registry: &Registry,
+    args: &ShowArgs,
+) -> Result<()> {
+    let mut built = build(cli, &mut config, registry, args).await?;
+    if cli.system.is_none() {
+        history::compare(&mut built);
+    }
+
+    match cli.format {
+        Format::Json => print_json(built),
+        _ => {
+            let options = render_options(cli, &config);
+            let items = built
+                .into_renderers()
+                .iter()
+                .flat_map(|segment| segment.summary(&options.theme))
+                .collect();
+            render_summary(items, &options)
+        }
+    }
+}
+
+fn list_segments(config: &Config, registry: &Registry) -> Result<()> {
+    let width = registry.names().map(str::len).max().unwrap_or(0);
+    let mut output = String::new();
+    for name in registry.names() {
+        let status = match config.segments.iter().position(|enabled| enabled == name) {
+            Some(index) => format!("enabled (#{})", index + 1),
+            None => "disabled".to_string(),
+        };
+        output.push_str(&format!("{name:width$}  {status}\n"));
+    }
+    print(&output)
+}
+
+fn check_config(config: &Config, registry: &Registry) -> Result<()> {
+    config.segments(registry)?;
+
+    let mut output = String::new();
+    if config.sources.is_empty() {
+        output.push_str("No configuration files found, using defaults\n");
+    }
+    for source in &config.sources {
+        output.push_str(&format!("Loaded {}\n", source.display()));
+    }
+    output.push_str(&format!(
+        "Configuration is valid: {} segments enabled\n",
+        config.segments.len()
+    ));
+    print(&output)
+}
+
+fn index_fortunes(databases: &[PathBuf], flags: index::Flags) -> Result<()> {
+    let mut output = String::new();
+    for database in databases {
+        let content =
+            fs::read(database).with_context(|| format!("Failed to read {}", database.display()))?;
+        let index = Index::build(&content, b'%', flags)
+            .with_context(|| format!("Failed to index {}", database.display()))?;
+
+        let path = Index::path_for(database);
+        let mut writer = BufWriter::new(
+            File::create(&path).with_context(|| format!("Failed to create {}", path.display()))?,
+        );
+        index
+            .write(&mut writer)
+            .and_then(|()| writer.flush())
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        output.push_str(&format!(
+            "Indexed {} fortunes into {}\n",
+            index.len(),
+            path.display()
+        ));
+    }
+    print(&output)
 }
@@ -0,0 +1,24 @@
+use std::path::PathBuf;
+
+/// `$XDG_CONFIG_HOME/welcome2u`, falling back to `~/.config/welcome2u` on every platform.
+pub fn config_dir() -> Option<PathBuf> {
+    xdg_dir("XDG_CONFIG_HOME", ".config")
+}
+
+/// `$XDG_CACHE_HOME/welcome2u`, falling back to `~/.cache/welcome2u` on every platform.
+pub fn cache_dir() -> Option<PathBuf> {
+    xdg_dir("XDG_CACHE_HOME", ".cache")
+}
+
+/// `$XDG_STATE_HOME/welcome2u`, falling back to `~/.local/state/welcome2u` on every platform.
+pub fn state_dir() -> Option<PathBuf> {
+    xdg_dir("XDG_STATE_HOME", ".local/state")
+}
+
+fn xdg_dir(variable: &str, fallback: &str) -> Option<PathBuf> {
+    std::env::var_os(variable)
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(fallback)))
+        .map(|dir| dir.join("welcome2u"))
+}
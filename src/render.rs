@@ -0,0 +1,228 @@
+use anyhow::Result;
+use clap::ValueEnum;
+use ratatui::backend::{CrosstermBackend, TestBackend};
+use ratatui::buffer::Buffer;
+use ratatui::layout::*;
+use ratatui::text::{Line, Span};
+use ratatui::{Frame, Terminal, TerminalOptions, Viewport};
+use segment::{text, ColorDepth, LayoutConfig, LayoutItem, Segment, Theme};
+use std::io::{stdout, IsTerminal};
+
+use crate::print;
+
+/// Width used for text output when it can't be taken from the terminal or `$COLUMNS`.
+const DEFAULT_TEXT_WIDTH: u16 = 80;
+
+/// Between the items of `welcome2u summary`.
+const SUMMARY_SEPARATOR: &str = " · ";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    /// `terminal` when stdout is an interactive terminal, otherwise `ansi` if `CLICOLOR_FORCE`
+    /// is set or `plain` if not
+    #[default]
+    Auto,
+    /// Draw inline in the terminal
+    Terminal,
+    /// Text with ANSI colors, e.g. to pre-generate /run/motd.dynamic
+    Ansi,
+    /// Text without any colors or styles
+    Plain,
+    /// The data collected by each segment, as JSON
+    Json,
+}
+
+impl Format {
+    fn resolve(self) -> Self {
+        match self {
+            Format::Auto => {
+                let dumb = std::env::var("TERM").is_ok_and(|term| term == "dumb");
+                let forced = std::env::var("CLICOLOR_FORCE")
+                    .is_ok_and(|value| !value.is_empty() && value != "0");
+                if stdout().is_terminal() && !dumb {
+                    Format::Terminal
+                } else if forced {
+                    Format::Ansi
+                } else {
+                    Format::Plain
+                }
+            }
+            format => format,
+        }
+    }
+}
+
+/// How `render_segments` lays out and prints the MOTD.
+#[derive(Debug, Clone)]
+pub struct RenderOptions {
+    pub format: Format,
+    pub theme: Theme,
+    /// Columns to lay out for, instead of the terminal's width.
+    pub width: Option<u16>,
+    /// Colors the output can show; other styles are kept either way.
+    pub colors: ColorDepth,
+    pub layout: LayoutConfig,
+}
+
+pub fn render_segments(segments: &[Box<dyn Segment>], options: &RenderOptions) -> Result<()> {
+    let segments: Vec<&dyn Segment> = segments.iter().map(Box::as_ref).collect();
+    let segments = segments.as_slice();
+    let text = match options.format.resolve() {
+        Format::Terminal => return render_terminal(segments, options),
+        format => {
+            let width = options.width.unwrap_or_else(text_width);
+            let mut buffer = render_buffer(segments, width, options)?;
+            options.colors.apply(&mut buffer);
+            match format {
+                Format::Ansi => text::to_ansi(&buffer),
+                Format::Plain => text::to_plain(&buffer),
+                _ => unreachable!("not a rendered format"),
+            }
+        }
+    };
+
+    print(&text)
+}
+
+/// Print segments' summary `items` on one line. Only `plain` leaves out colors, since a
+/// summary is printed as text even on a terminal.
+pub fn render_summary(items: Vec<Line>, options: &RenderOptions) -> Result<()> {
+    let mut spans = Vec::new();
+    for (index, item) in items.into_iter().enumerate() {
+        if index > 0 {
+            spans.push(Span::raw(SUMMARY_SEPARATOR));
+        }
+        let style = item.style;
+        spans.extend(
+            item.spans
+                .into_iter()
+                .map(|span| Span::styled(span.content, style.patch(span.style))),
+        );
+    }
+    let line = Line::from(spans);
+
+    let width = line.width() as u16;
+    let mut buffer = Buffer::empty(Rect::new(0, 0, width, 1));
+    buffer.set_line(0, 0, &line, width);
+    options.colors.apply(&mut buffer);
+
+    let text = match options.format.resolve() {
+        Format::Plain => text::to_plain(&buffer),
+        _ => text::to_ansi(&buffer),
+    };
+    print(&text)
+}
+
+fn render_terminal(segments: &[&dyn Segment], options: &RenderOptions) -> Result<()> {
+    let (terminal_width, _) = ratatui::crossterm::terminal::size()?;
+    let width = options
+        .width
+        .map_or(terminal_width, |width| width.min(terminal_width));
+    let (areas, height) = arrange(segments, width, options);
+
+    let backend = CrosstermBackend::new(stdout());
+    let mut terminal = Terminal::with_options(
+        backend,
+        TerminalOptions {
+            viewport: Viewport::Inline(height),
+        },
+    )?;
+    let mut result = Ok(());
+    terminal.draw(|frame| {
+        let origin = frame.area();
+        result = draw_areas(frame, origin, segments, &areas, &options.theme);
+        options.colors.apply(frame.buffer_mut());
+    })?;
+
+    result
+}
+
+/// Render `segments` into an off-screen buffer `width` columns wide.
+pub fn render_buffer(
+    segments: &[&dyn Segment],
+    width: u16,
+    options: &RenderOptions,
+) -> Result<Buffer> {
+    let (areas, height) = arrange(segments, width, options);
+    if height == 0 {
+        return Ok(Buffer::empty(Rect::new(0, 0, width, 0)));
+    }
+
+    let mut terminal = Terminal::new(TestBackend::new(width, height))?;
+    let mut result = Ok(());
+    terminal.draw(|frame| {
+        let origin = frame.area();
+        result = draw_areas(frame, origin, segments, &areas, &options.theme)
+    })?;
+    result?;
+
+    Ok(terminal.backend().buffer().clone())
+}
+
+/// Lay `segments` out in as many columns as fit in `width`, returning each one's area relative
+/// to the top left corner, and the height they take altogether.
+fn arrange(segments: &[&dyn Segment], width: u16, options: &RenderOptions) -> (Vec<Rect>, u16) {
+    let layout = &options.layout;
+    let columns = layout.column_count(width);
+    let column_width = layout.column_width(width, columns);
+    let items: Vec<LayoutItem> = segments
+        .iter()
+        .map(|segment| {
+            let full_width = segment.full_width();
+            LayoutItem {
+                height: segment.height(
+                    if full_width { width } else { column_width },
+                    &options.theme,
+                ),
+                full_width,
+            }
+        })
+        .collect();
+    segment::arrange(&items, Rect::new(0, 0, width, 0), columns, layout)
+}
+
+/// Lay `segments` out in `area` and draw them, clipping whatever doesn't fit.
+pub fn draw(
+    frame: &mut Frame,
+    area: Rect,
+    segments: &[&dyn Segment],
+    options: &RenderOptions,
+) -> Result<()> {
+    let (areas, _) = arrange(segments, area.width, options);
+    draw_areas(frame, area, segments, &areas, &options.theme)
+}
+
+fn draw_areas(
+    frame: &mut Frame,
+    origin: Rect,
+    segments: &[&dyn Segment],
+    areas: &[Rect],
+    theme: &Theme,
+) -> Result<()> {
+    for (segment, area) in segments.iter().zip(areas) {
+        let area = Rect {
+            x: origin.x + area.x,
+            y: origin.y + area.y,
+            ..*area
+        }
+        .intersection(origin);
+        segment.render(frame, area, theme)?;
+    }
+
+    Ok(())
+}
+
+/// The terminal's width when there is one, otherwise `$COLUMNS`, so piped output still fits.
+fn text_width() -> u16 {
+    if stdout().is_terminal() {
+        if let Ok((width, _)) = ratatui::crossterm::terminal::size() {
+            return width;
+        }
+    }
+
+    std::env::var("COLUMNS")
+        .ok()
+        .and_then(|columns| columns.parse().ok())
+        .filter(|&columns| columns > 0)
+        .unwrap_or(DEFAULT_TEXT_WIDTH)
+}
@@ -0,0 +1,42 @@
+//! Small JSON files in `$XDG_STATE_HOME/welcome2u` that remember what earlier runs saw.
+//!
+//! Losing them only costs a run its memory, so unreadable files are treated as empty.
+
+use anyhow::{Context, Result};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fs;
+use tracing::debug;
+
+use crate::paths;
+
+/// Read the state file `name`, or the default when it's missing or unreadable.
+pub fn load<T: DeserializeOwned + Default>(name: &str) -> T {
+    let Some(path) = paths::state_dir().map(|dir| dir.join(name)) else {
+        return T::default();
+    };
+    match fs::read(&path).map(|bytes| serde_json::from_slice(&bytes)) {
+        Ok(Ok(value)) => value,
+        Ok(Err(error)) => {
+            debug!("ignoring unreadable state {}: {error}", path.display());
+            T::default()
+        }
+        Err(_) => T::default(),
+    }
+}
+
+/// Replace the state file `name` with `value`.
+pub fn save<T: Serialize>(name: &str, value: &T) -> Result<()> {
+    let Some(directory) = paths::state_dir() else {
+        return Ok(());
+    };
+    fs::create_dir_all(&directory)
+        .with_context(|| format!("Failed to create {}", directory.display()))?;
+
+    // Write to a temporary file first so a concurrent login never reads half of it
+    let path = directory.join(name);
+    let temporary = path.with_extension(format!("json.{}", std::process::id()));
+    fs::write(&temporary, serde_json::to_vec(value)?)
+        .with_context(|| format!("Failed to write {}", temporary.display()))?;
+    fs::rename(&temporary, &path).with_context(|| format!("Failed to write {}", path.display()))
+}
@@ -0,0 +1,252 @@
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tracing::{debug, warn};
+
+use crate::state;
+
+const STATE_FILE: &str = "throttle.json";
+
+/// SSH connections and tmux sessions remembered in the state file; older ones are forgotten
+/// first.
+const MAX_SESSIONS: usize = 32;
+
+/// When a bare `welcome2u` shows the full MOTD, from the configuration's `[throttle]` table.
+/// The full MOTD is shown when any of the enabled rules asks for it, and `otherwise` when none
+/// does.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ThrottleConfig {
+    /// Show the full MOTD once this long has passed since it was last shown.
+    #[serde(with = "humantime_serde")]
+    pub interval: Option<Duration>,
+    /// Show the full MOTD the first time it runs each day.
+    pub first_login_of_day: bool,
+    /// Show the full MOTD in the first shell of each SSH connection.
+    pub new_ssh_session: bool,
+    /// What every tmux pane but the first in each session shows, whatever the other rules say.
+    pub tmux: Option<Mode>,
+    /// What to show when no rule asks for the full MOTD.
+    pub otherwise: Mode,
+}
+
+impl Default for ThrottleConfig {
+    fn default() -> Self {
+        Self {
+            interval: None,
+            first_login_of_day: true,
+            new_ssh_session: true,
+            tmux: None,
+            otherwise: Mode::Compact,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Mode {
+    /// The whole MOTD.
+    Full,
+    /// One line, like `welcome2u summary`.
+    Compact,
+    /// Nothing at all.
+    Silent,
+}
+
+/// What the last runs saw, kept in `$XDG_STATE_HOME/welcome2u/throttle.json`.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct State {
+    /// When the full MOTD was last shown, in seconds since the Unix epoch.
+    pub last_full: Option<i64>,
+    /// `$SSH_CONNECTION` of connections that already showed something.
+    pub ssh_connections: Vec<String>,
+    /// `$TMUX` of sessions that already showed something.
+    pub tmux_sessions: Vec<String>,
+}
+
+/// The shell the MOTD is being shown in, from its environment.
+#[derive(Debug, Default, Clone)]
+pub struct Session {
+    pub ssh_connection: Option<String>,
+    pub tmux: Option<String>,
+}
+
+impl Session {
+    pub fn from_env() -> Self {
+        let var = |name| std::env::var(name).ok().filter(|value| !value.is_empty());
+        Self {
+            ssh_connection: var("SSH_CONNECTION"),
+            tmux: var("TMUX"),
+        }
+    }
+}
+
+/// Decide what to show in `session`, and update `state` to remember it.
+pub fn decide(
+    config: &ThrottleConfig,
+    state: &mut State,
+    session: &Session,
+    now: DateTime<Local>,
+) -> Mode {
+    let new_ssh_session = session
+        .ssh_connection
+        .as_ref()
+        .is_some_and(|connection| remember(&mut state.ssh_connections, connection));
+
+    if let (Some(mode), Some(tmux)) = (config.tmux, &session.tmux) {
+        if !remember(&mut state.tmux_sessions, tmux) {
+            return mode;
+        }
+    }
+
+    let last_full = state
+        .last_full
+        .and_then(|seconds| DateTime::from_timestamp(seconds, 0))
+        .map(|time| time.with_timezone(&Local));
+    let full = match last_full {
+        None => true,
+        Some(last_full) => {
+            (config.new_ssh_session && new_ssh_session)
+                || (config.first_login_of_day && last_full.date_naive() < now.date_naive())
+                || config.interval.is_some_and(|interval| {
+                    (now - last_full).to_std().unwrap_or_default() >= interval
+                })
+        }
+    };
+
+    if full {
+        state.last_full = Some(now.timestamp());
+        Mode::Full
+    } else {
+        config.otherwise
+    }
+}
+
+/// Add `session` to `seen` unless it's there already, returning whether it was new.
+fn remember(seen: &mut Vec<String>, session: &str) -> bool {
+    if seen.iter().any(|known| known == session) {
+        return false;
+    }
+    seen.push(session.to_string());
+    let excess = seen.len().saturating_sub(MAX_SESSIONS);
+    seen.drain(..excess);
+    true
+}
+
+/// Decide what to show in this shell, recording it in the state file. Problems with the state
+/// file are logged and treated as if it were empty, so they never cost a login its MOTD.
+pub fn check(config: &ThrottleConfig) -> Mode {
+    let mut state: State = state::load(STATE_FILE);
+
+    let mode = decide(config, &mut state, &Session::from_env(), Local::now());
+    debug!(?mode, "throttled MOTD");
+
+    if let Err(error) = state::save(STATE_FILE, &state) {
+        warn!("{error:#}");
+    }
+    mode
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(hour: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(2024, 3, 14, hour, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn full_once_per_day_then_compact() {
+        let config = ThrottleConfig::default();
+        let mut state = State::default();
+        let session = Session::default();
+
+        assert_eq!(decide(&config, &mut state, &session, at(9)), Mode::Full);
+        assert_eq!(decide(&config, &mut state, &session, at(17)), Mode::Compact);
+        let tomorrow = at(9) + chrono::Duration::days(1);
+        assert_eq!(decide(&config, &mut state, &session, tomorrow), Mode::Full);
+    }
+
+    #[test]
+    fn full_after_interval_or_new_ssh_connection() {
+        let config = ThrottleConfig {
+            interval: Some(Duration::from_secs(4 * 60 * 60)),
+            first_login_of_day: false,
+            otherwise: Mode::Silent,
+            ..ThrottleConfig::default()
+        };
+        let mut state = State::default();
+
+        assert_eq!(decide(&config, &mut state, &ssh("a"), at(9)), Mode::Full);
+        assert_eq!(decide(&config, &mut state, &ssh("a"), at(10)), Mode::Silent);
+        assert_eq!(decide(&config, &mut state, &ssh("b"), at(11)), Mode::Full);
+        assert_eq!(decide(&config, &mut state, &ssh("b"), at(15)), Mode::Full);
+    }
+
+    fn ssh(connection: &str) -> Session {
+        Session {
+            ssh_connection: Some(connection.to_string()),
+            tmux: None,
+        }
+    }
+
+    fn ssh_only() -> ThrottleConfig {
+        ThrottleConfig {
+            first_login_of_day: false,
+            ..ThrottleConfig::default()
+        }
+    }
+
+    #[test]
+    fn local_shells_dont_make_ssh_sessions_new() {
+        let config = ssh_only();
+        let mut state = State::default();
+        let local = Session::default();
+
+        assert_eq!(decide(&config, &mut state, &ssh("a"), at(9)), Mode::Full);
+        assert_eq!(decide(&config, &mut state, &local, at(10)), Mode::Compact);
+        assert_eq!(
+            decide(&config, &mut state, &ssh("a"), at(11)),
+            Mode::Compact
+        );
+    }
+
+    #[test]
+    fn concurrent_ssh_connections_are_each_new_once() {
+        let config = ssh_only();
+        let mut state = State::default();
+
+        assert_eq!(decide(&config, &mut state, &ssh("a"), at(9)), Mode::Full);
+        assert_eq!(decide(&config, &mut state, &ssh("b"), at(9)), Mode::Full);
+        for hour in 10..13 {
+            assert_eq!(
+                decide(&config, &mut state, &ssh("a"), at(hour)),
+                Mode::Compact
+            );
+            assert_eq!(
+                decide(&config, &mut state, &ssh("b"), at(hour)),
+                Mode::Compact
+            );
+        }
+    }
+
+    #[test]
+    fn later_tmux_panes_follow_tmux_rule() {
+        let config = ThrottleConfig {
+            tmux: Some(Mode::Silent),
+            ..ThrottleConfig::default()
+        };
+        let mut state = State::default();
+        let pane = Session {
+            ssh_connection: None,
+            tmux: Some("/tmp/tmux-1000/default,4242,0".to_string()),
+        };
+
+        assert_eq!(decide(&config, &mut state, &pane, at(9)), Mode::Full);
+        assert_eq!(decide(&config, &mut state, &pane, at(9)), Mode::Silent);
+        let tomorrow = at(9) + chrono::Duration::days(1);
+        assert_eq!(decide(&config, &mut state, &pane, tomorrow), Mode::Silent);
+    }
+}
@@ -0,0 +1,215 @@
+//! `welcome2u watch`: the MOTD as a fullscreen dashboard that keeps itself up to date.
+
+use anyhow::{anyhow, bail, Result};
+use futures::StreamExt;
+use humantime::format_duration;
+use ratatui::backend::CrosstermBackend;
+use ratatui::crossterm::event::{Event, EventStream, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::crossterm::execute;
+use ratatui::crossterm::terminal::{
+    disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
+};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::text::Line;
+use ratatui::{Frame, Terminal};
+use segment::{Segment, SegmentInfo, UnavailableSegment};
+use std::io::{stdout, IsTerminal, Stdout};
+use std::time::Duration;
+use tokio::time::{Instant, MissedTickBehavior};
+use tracing::{info_span, warn};
+
+use crate::build::{build_segments, spawn_build, BuildTask};
+use crate::config::{ConfiguredSegment, OnError};
+use crate::render::{self, RenderOptions};
+
+/// How often to check on builders between key presses.
+const TICK: Duration = Duration::from_millis(200);
+
+/// Show `segments` fullscreen, building each one again every `watch_interval` and redrawing
+/// when it changes, until `q` is pressed.
+pub async fn watch(
+    segments: Vec<ConfiguredSegment>,
+    budget: Option<Duration>,
+    options: &RenderOptions,
+) -> Result<()> {
+    if !stdout().is_terminal() {
+        bail!("watch needs an interactive terminal");
+    }
+
+    let built = build_segments(segments.clone(), budget, None).await?;
+    let started = Instant::now();
+    let mut slots: Vec<Slot> = segments
+        .into_iter()
+        .zip(built.segments)
+        .map(|(segment, built)| Slot {
+            next_build: segment.watch_interval.map(|interval| started + interval),
+            renderer: built.into_renderer(),
+            building: None,
+            segment,
+        })
+        .collect();
+
+    let mut screen = Screen::enter()?;
+    let mut events = EventStream::new();
+    let mut ticks = tokio::time::interval(TICK);
+    ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    let mut changed = true;
+    loop {
+        if changed {
+            let mut result = Ok(());
+            screen
+                .terminal
+                .draw(|frame| result = draw(frame, &slots, options))?;
+            result?;
+        }
+
+        changed = false;
+        tokio::select! {
+            event = events.next() => match event.transpose()? {
+                None => break,
+                Some(Event::Key(key)) if key.kind == KeyEventKind::Press => match key.code {
+                    KeyCode::Char('q') | KeyCode::Esc => break,
+                    KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => break,
+                    KeyCode::Char('r') => {
+                        let now = Instant::now();
+                        for slot in &mut slots {
+                            slot.next_build = Some(now);
+                        }
+                    }
+                    _ => {}
+                },
+                Some(Event::Resize(..)) => changed = true,
+                Some(_) => {}
+            },
+            _ = ticks.tick() => {}
+        }
+
+        for slot in &mut slots {
+            changed |= slot.poll().await;
+        }
+    }
+
+    Ok(())
+}
+
+/// One segment on the dashboard: what it shows now, and when it's built next.
+struct Slot {
+    segment: ConfiguredSegment,
+    renderer: Option<Box<dyn Segment>>,
+    /// When to start building it again, if ever.
+    next_build: Option<Instant>,
+    /// The build in progress, and when it started.
+    building: Option<(BuildTask, Instant)>,
+}
+
+impl Slot {
+    /// Start a build when one is due and collect it once it's done, returning whether what the
+    /// segment shows changed.
+    async fn poll(&mut self) -> bool {
+        let now = Instant::now();
+
+        if let Some((task, started)) = &mut self.building {
+            if task.is_finished() {
+                let result = match task.await {
+                    Ok(result) => result,
+                    Err(error) => Err(error.into()),
+                };
+                self.building = None;
+                return self.finish(result);
+            }
+
+            if let Some(timeout) = self.segment.timeout {
+                if now >= *started + timeout {
+                    task.abort();
+                    self.building = None;
+                    return self
+                        .finish(Err(anyhow!("timed out after {}", format_duration(timeout))));
+                }
+            }
+            return false;
+        }
+
+        if self.next_build.is_some_and(|at| now >= at) {
+            let span = info_span!("segment", name = self.segment.name);
+            let task = spawn_build(
+                self.segment.name.clone(),
+                self.segment.builder.clone(),
+                span,
+            );
+            self.building = Some((task, now));
+            self.next_build = self.segment.watch_interval.map(|interval| now + interval);
+        }
+        false
+    }
+
+    /// Show freshly built info. A failed build keeps showing the last info, if there is any,
+    /// since a dashboard shouldn't flicker or quit over one bad refresh.
+    fn finish(&mut self, result: Result<Box<dyn SegmentInfo>>) -> bool {
+        match result {
+            Ok(info) => {
+                self.renderer = Some(info.into_segment());
+                true
+            }
+            Err(error) => {
+                warn!(
+                    segment = self.segment.name.as_str(),
+                    "failed to refresh: {error:#}"
+                );
+                if self.renderer.is_some() || self.segment.on_error == OnError::Hide {
+                    return false;
+                }
+                self.renderer = Some(Box::new(UnavailableSegment::new(
+                    &self.segment.name,
+                    format!("{error:#}"),
+                )));
+                true
+            }
+        }
+    }
+}
+
+fn draw(frame: &mut Frame, slots: &[Slot], options: &RenderOptions) -> Result<()> {
+    let mut area = frame.area();
+    if let Some(width) = options.width {
+        area.width = area.width.min(width);
+    }
+    let [body, footer] = Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(area);
+
+    let segments: Vec<&dyn Segment> = slots
+        .iter()
+        .filter_map(|slot| slot.renderer.as_deref())
+        .collect();
+    render::draw(frame, body, &segments, options)?;
+    frame.render_widget(
+        Line::styled(" q quit · r refresh", options.theme.dim),
+        footer,
+    );
+
+    options.colors.apply(frame.buffer_mut());
+    Ok(())
+}
+
+/// The terminal in raw mode on the alternate screen, restored when dropped, including when
+/// unwinding from a panic.
+struct Screen {
+    terminal: Terminal<CrosstermBackend<Stdout>>,
+}
+
+impl Screen {
+    fn enter() -> Result<Self> {
+        let screen = Self {
+            terminal: Terminal::new(CrosstermBackend::new(stdout()))?,
+        };
+        enable_raw_mode()?;
+        execute!(stdout(), EnterAlternateScreen)?;
+        Ok(screen)
+    }
+}
+
+impl Drop for Screen {
+    fn drop(&mut self) {
+        let _ = disable_raw_mode();
+        let _ = execute!(stdout(), LeaveAlternateScreen);
+        let _ = self.terminal.show_cursor();
+    }
+}